@@ -0,0 +1,207 @@
+use crate::fixtures::{self, Fixture};
+use std::path::Path;
+
+/// Example and answer found in one part of a puzzle page
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PartExample {
+    /// `None` when neither this part nor an earlier one has an example
+    pub example: Option<String>,
+    pub answer: Option<String>,
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn strip_tags(s: &str) -> String {
+    let mut result = String::new();
+    let mut in_tag = false;
+    for ch in s.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => result.push(ch),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// All `(start, content)` of `open ... close` sections in `html`
+fn sections<'a>(html: &'a str, open: &str, close: &str) -> Vec<(usize, &'a str)> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = html[offset..].find(open) {
+        let start = offset + start + open.len();
+        let Some(end) = html[start..].find(close) else {
+            break;
+        };
+        result.push((start, &html[start..start + end]));
+        offset = start + end + close.len();
+    }
+    result
+}
+
+/// Pick the example of every `<article class="day-desc">` on a saved puzzle page.
+///
+/// The expected answer is the last emphasised `<code><em>` of the article, and the
+/// example is the last `<pre><code>` block before it. This matches how the puzzles
+/// usually end with "... the answer is **142**". A part without an example of its own
+/// reuses the one of the part before it, as part two often does. Parts that only
+/// illustrate the example (e.g. with extra annotations) still need the fixtures
+/// checked by hand.
+pub fn extract_parts(html: &str) -> Vec<PartExample> {
+    let mut previous = None;
+    sections(html, "<article class=\"day-desc\">", "</article>")
+        .into_iter()
+        .map(|(_, article)| {
+            let answer = sections(article, "<code><em>", "</em></code>")
+                .last()
+                .map(|&(i, s)| (i, decode_entities(&strip_tags(s))));
+            let limit = answer.as_ref().map(|&(i, _)| i).unwrap_or(article.len());
+            let example = sections(article, "<pre><code>", "</code></pre>")
+                .into_iter()
+                .rfind(|&(i, _)| i < limit)
+                .map(|(_, s)| decode_entities(&strip_tags(s)))
+                .or(previous.take());
+            previous = example.clone();
+            PartExample {
+                example,
+                answer: answer.map(|(_, a)| a),
+            }
+        })
+        .collect()
+}
+
+/// Whether `name` is one `write_fixtures` would pick: `example`, `example2`, ...
+fn is_generated(name: &str) -> bool {
+    name.strip_prefix("example")
+        .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Write the parts as fixtures, sharing one fixture when both parts use the same example.
+/// Fixtures written by an earlier run are removed first, so none are left over from a page
+/// with more examples.
+pub fn write_fixtures(root: &Path, day: &str, parts: &[PartExample]) -> Vec<Fixture> {
+    let dir = fixtures::fixture_dir(root, day);
+    for fixture in fixtures::find_fixtures(root, day) {
+        if is_generated(&fixture.name) {
+            std::fs::remove_file(&fixture.input).unwrap();
+            let _ = std::fs::remove_file(fixture.input.with_extension("answers"));
+        }
+    }
+    let mut result: Vec<(String, Vec<Option<String>>)> = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let Some(example) = &part.example else {
+            continue;
+        };
+        match result.iter_mut().find(|(e, _)| e == example) {
+            Some((_, answers)) => answers[i] = part.answer.clone(),
+            None => {
+                let mut answers = vec![None; parts.len()];
+                answers[i] = part.answer.clone();
+                result.push((example.clone(), answers));
+            }
+        }
+    }
+    result
+        .into_iter()
+        .enumerate()
+        .map(|(i, (example, answers))| {
+            let name = match i {
+                0 => "example".to_string(),
+                _ => format!("example{}", i + 1),
+            };
+            let input = dir.join(format!("{}.txt", name));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(&input, example).unwrap();
+            std::fs::write(
+                input.with_extension("answers"),
+                fixtures::format_answers(&answers),
+            )
+            .unwrap();
+            Fixture {
+                name,
+                input,
+                answers,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A made-up page showing a new example in part two
+    const OWN_EXAMPLES: &str = include_str!("../testdata/day1.html");
+    /// A made-up page going back to the example of part one
+    const SHARED_EXAMPLE: &str = include_str!("../testdata/day2.html");
+
+    fn answers(parts: &[PartExample]) -> Vec<Option<&str>> {
+        parts.iter().map(|p| p.answer.as_deref()).collect()
+    }
+
+    #[test]
+    fn each_part_has_its_own_example() {
+        let parts = extract_parts(OWN_EXAMPLES);
+        assert_eq!(answers(&parts), [Some("10"), Some("16")]);
+        assert!(parts[0]
+            .example
+            .as_ref()
+            .unwrap()
+            .starts_with("a1b2\n3<4\n"));
+        assert!(parts[1].example.as_ref().unwrap().starts_with("x9y1\n"));
+    }
+
+    #[test]
+    fn part_two_reuses_the_example() {
+        let parts = extract_parts(SHARED_EXAMPLE);
+        assert_eq!(answers(&parts), [Some("4"), Some("12")]);
+        assert!(parts[0].example.as_ref().unwrap().starts_with("Stack 1: "));
+        assert_eq!(parts[1].example, parts[0].example);
+    }
+
+    #[test]
+    fn no_example_before_the_first_part() {
+        let html = "<article class=\"day-desc\"><p><code><em>1</em></code></p></article>";
+        assert_eq!(
+            extract_parts(html),
+            [PartExample {
+                example: None,
+                answer: Some("1".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn write_fixtures_replaces_earlier_ones() {
+        let root = std::env::temp_dir().join(format!("aoc-extract-{}", std::process::id()));
+        let dir = fixtures::fixture_dir(&root, "d1");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["example", "example2", "example3", "mine"] {
+            std::fs::write(dir.join(format!("{}.txt", name)), "stale").unwrap();
+            std::fs::write(dir.join(format!("{}.answers", name)), "-\n-\n").unwrap();
+        }
+        let written = write_fixtures(&root, "d1", &extract_parts(SHARED_EXAMPLE));
+        assert_eq!(written.len(), 1);
+        assert_eq!(
+            written[0].answers,
+            [Some("4".to_string()), Some("12".to_string())]
+        );
+        let names = fixtures::find_fixtures(&root, "d1")
+            .into_iter()
+            .map(|f| f.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["example", "mine"]);
+        assert!(!dir.join("example3.answers").exists());
+        let written = write_fixtures(&root, "d1", &extract_parts(OWN_EXAMPLES));
+        assert_eq!(written.len(), 2);
+        assert_eq!(written[1].answers, [None, Some("16".to_string())]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
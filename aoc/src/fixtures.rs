@@ -0,0 +1,137 @@
+use crate::run;
+use std::path::{Path, PathBuf};
+
+/// An example input in `dN/fixtures/<name>.txt` with its expected answers in `<name>.answers`.
+///
+/// The answers file has one line per part. A `-` or empty line leaves that part unchecked.
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub name: String,
+    pub input: PathBuf,
+    pub answers: Vec<Option<String>>,
+}
+
+pub fn fixture_dir(root: &Path, day: &str) -> PathBuf {
+    root.join(day).join("fixtures")
+}
+
+pub fn parse_answers(contents: &str) -> Vec<Option<String>> {
+    contents
+        .lines()
+        .map(|l| match l.trim() {
+            "" | "-" => None,
+            answer => Some(answer.to_string()),
+        })
+        .collect()
+}
+
+pub fn format_answers(answers: &[Option<String>]) -> String {
+    answers
+        .iter()
+        .map(|a| a.as_deref().unwrap_or("-").to_string() + "\n")
+        .collect()
+}
+
+pub fn find_fixtures(root: &Path, day: &str) -> Vec<Fixture> {
+    let dir = fixture_dir(root, day);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return vec![];
+    };
+    let mut fixtures = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "txt"))
+        .map(|input| {
+            let answers = std::fs::read_to_string(input.with_extension("answers"))
+                .map(|s| parse_answers(&s))
+                .unwrap_or_default();
+            Fixture {
+                name: input.file_stem().unwrap().to_string_lossy().to_string(),
+                input,
+                answers,
+            }
+        })
+        .collect::<Vec<_>>();
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    fixtures
+}
+
+/// Run a day on a fixture and list the mismatching answers. A day may fail on the parts the
+/// fixture leaves unchecked, as when the example of part two has no answer for part one.
+pub fn check(
+    root: &Path,
+    day: &str,
+    fixture: &Fixture,
+    options: &run::RunOptions,
+) -> Result<Vec<String>, String> {
+    // The days read `input.txt` from their working directory, so give them one of their own
+    let dir = std::env::temp_dir().join(format!(
+        "aoc-{}-{}-{}",
+        day,
+        fixture.name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(&fixture.input, dir.join("input.txt")).unwrap();
+    let output = run::command(root, day, &dir, options).output();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let output = output.map_err(|e| e.to_string())?;
+    let answers = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    let mut errors = mismatches(&fixture.answers, &answers);
+    if !errors.is_empty() && !output.status.success() {
+        errors.push(run::error_message(&String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(errors)
+}
+
+/// The expected answers that the printed ones don't match
+fn mismatches(expected: &[Option<String>], answers: &[String]) -> Vec<String> {
+    expected
+        .iter()
+        .enumerate()
+        .filter_map(|(i, expected)| {
+            let expected = expected.as_ref()?;
+            let actual = answers.get(i).map(|s| s.as_str()).unwrap_or("");
+            match actual == expected {
+                true => None,
+                false => Some(format!(
+                    "part {}: expected {}, got {}",
+                    i + 1,
+                    expected,
+                    actual
+                )),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_round_trip() {
+        let answers = vec![None, Some("281".to_string())];
+        assert_eq!(format_answers(&answers), "-\n281\n");
+        assert_eq!(parse_answers("-\n281\n"), answers);
+        assert_eq!(parse_answers("\n 5 \n"), [None, Some("5".to_string())]);
+    }
+
+    #[test]
+    fn unchecked_parts_are_skipped() {
+        let expected = parse_answers("-\n281\n");
+        let printed = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        assert!(mismatches(&expected, &printed(&["-", "281"])).is_empty());
+        assert_eq!(
+            mismatches(&expected, &printed(&["142", "280"])),
+            ["part 2: expected 281, got 280"]
+        );
+        assert_eq!(
+            mismatches(&expected, &printed(&["142"])),
+            ["part 2: expected 281, got "]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+mod extract;
+mod fixtures;
+mod new;
+mod report;
+mod run;
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Runner for the Advent of Code 2023 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run days on their `input.txt` and report answers, timings and counters
+    Run {
+        /// Days to run, e.g. `17` or `d17` (all days if omitted)
+        days: Vec<String>,
+        /// Print the report as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// Run every strategy of the days that have several, failing if they disagree
+        #[arg(long)]
+        cross_check: bool,
+    },
+    /// Run days and write a standalone HTML report with their visualisations
+    Report {
+        /// Days to include (all days if omitted)
+        days: Vec<String>,
+        /// Where to write the report
+        #[arg(short, long, default_value = "report.html")]
+        output: PathBuf,
+    },
+    /// Check days against the example fixtures in `dN/fixtures`
+    Test {
+        /// Days to test (all days if omitted)
+        days: Vec<String>,
+        /// Run every strategy of the days that have several, failing if they disagree
+        #[arg(long)]
+        cross_check: bool,
+    },
+    /// Extract example fixtures from a puzzle page saved from the website
+    Fixtures {
+        /// Day the page belongs to, e.g. `5` or `d5`
+        day: String,
+        /// The saved HTML page
+        page: PathBuf,
+        /// Overwrite existing fixtures
+        #[arg(long)]
+        force: bool,
+    },
+    /// Create a new day crate from the template and add it to the workspace
+    New {
+        /// Day to create, e.g. `22` or `d22`
+        day: String,
+    },
+}
+
+/// Answers, then each phase's time and counters
+fn table(run: &run::DayRun) -> String {
+    let mut result = format!("{} ({:.2}ms)\n", run.day, run.elapsed_ms);
+    result += &format!("  answers: {}\n", run.answers.join(", "));
+    for phase in &run.phases {
+        let counters = run
+            .counters
+            .iter()
+            .filter(|c| c.phase.as_deref() == Some(phase.name.as_str()))
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>();
+        let line = format!(
+            "  {:<8}{:>12}  {}",
+            phase.name,
+            phase.busy,
+            counters.join(" ")
+        );
+        result += line.trim_end();
+        result += "\n";
+    }
+    result
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Run {
+            days,
+            json,
+            cross_check,
+        } => {
+            let root = run::workspace_root();
+            let days = run::find_days(&root, &days);
+            run::build(&root, &days);
+            let options = run::RunOptions {
+                cross_check,
+                ..Default::default()
+            };
+            let mut runs = Vec::new();
+            for day in days {
+                match run::run_day(&root, &day, &options) {
+                    Ok(run) => {
+                        if !json {
+                            print!("{}", table(&run));
+                        }
+                        runs.push(run);
+                    }
+                    Err(e) => eprintln!("{}: {}", day, e),
+                }
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&runs).unwrap());
+            }
+        }
+        Command::Report { days, output } => {
+            let root = run::workspace_root();
+            let days = run::find_days(&root, &days);
+            run::build(&root, &days);
+            let options = run::RunOptions {
+                visualise: true,
+                ..Default::default()
+            };
+            let results = days
+                .into_iter()
+                .map(|day| {
+                    let result = run::run_day(&root, &day, &options);
+                    (day, result)
+                })
+                .collect::<Vec<_>>();
+            std::fs::write(&output, report::render(&results)).unwrap();
+            println!("Wrote {}", output.display());
+        }
+        Command::Test { days, cross_check } => {
+            let root = run::workspace_root();
+            let days = run::find_days(&root, &days);
+            run::build(&root, &days);
+            let options = run::RunOptions {
+                cross_check,
+                ..Default::default()
+            };
+            let mut failed = 0;
+            for day in days {
+                for fixture in fixtures::find_fixtures(&root, &day) {
+                    let errors = match fixtures::check(&root, &day, &fixture, &options) {
+                        Ok(mismatches) => mismatches,
+                        Err(e) => vec![e],
+                    };
+                    match errors.is_empty() {
+                        true => println!("{} {} ... ok", day, fixture.name),
+                        false => {
+                            failed += 1;
+                            println!("{} {} ... FAILED", day, fixture.name);
+                            errors.iter().for_each(|e| println!("    {}", e));
+                        }
+                    }
+                }
+            }
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Fixtures { day, page, force } => {
+            let root = run::workspace_root();
+            let day = run::normalize_day(&day);
+            if !force && !fixtures::find_fixtures(&root, &day).is_empty() {
+                eprintln!("{} already has fixtures, use --force to overwrite", day);
+                std::process::exit(1);
+            }
+            let html = std::fs::read_to_string(&page).unwrap();
+            let parts = extract::extract_parts(&html);
+            if parts.iter().all(|p| p.example.is_none()) {
+                eprintln!("No examples found in {}", page.display());
+                std::process::exit(1);
+            }
+            for fixture in extract::write_fixtures(&root, &day, &parts) {
+                println!(
+                    "{}: {}",
+                    fixture.input.display(),
+                    fixtures::format_answers(&fixture.answers)
+                        .lines()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+        Command::New { day } => {
+            let root = run::workspace_root();
+            let day = run::normalize_day(&day);
+            match new::new_day(&root, &day) {
+                Ok(()) => println!("Created {}", day),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_table() {
+        let stderr = [
+            r#"{"timestamp":"t","level":"INFO","fields":{"message":"close","time.busy":"1.20ms","time.idle":"3.00µs"},"target":"d9","span":{"name":"parse"},"spans":[]}"#,
+            r#"{"timestamp":"t","level":"INFO","fields":{"counter":"pops","value":120},"target":"counters","span":{"name":"part1"},"spans":[{"name":"part1"}]}"#,
+            r#"{"timestamp":"t","level":"INFO","fields":{"counter":"pushes","value":300},"target":"counters","span":{"name":"part1"},"spans":[{"name":"part1"}]}"#,
+            r#"{"timestamp":"t","level":"INFO","fields":{"message":"close","time.busy":"15.3ms","time.idle":"2.00µs"},"target":"d9","span":{"name":"part1"},"spans":[]}"#,
+            r#"{"timestamp":"t","level":"INFO","fields":{"message":"close","time.busy":"800µs","time.idle":"1.00µs"},"target":"d9","span":{"name":"part2"},"spans":[]}"#,
+        ]
+        .join("\n");
+        let events = run::parse_events(&stderr);
+        let run = run::DayRun {
+            day: "d9".to_string(),
+            answers: vec!["114".to_string(), "2".to_string()],
+            elapsed_ms: 20.0,
+            input_lines: 3,
+            input_bytes: 40,
+            phases: events.phases,
+            counters: events.counters,
+            visualisations: events.visualisations,
+        };
+        assert_eq!(
+            table(&run),
+            "d9 (20.00ms)\n  answers: 114, 2\n  parse         1.20ms\n  part1         15.3ms  pops=120 pushes=300\n  part2          800µs\n"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::Path;
+
+const CARGO_TEMPLATE: &str = include_str!("../template/Cargo.toml");
+const MAIN_TEMPLATE: &str = include_str!("../template/main.rs");
+const LIB_TEMPLATE: &str = include_str!("../template/lib.rs");
+const BENCH_TEMPLATE: &str = include_str!("../template/bench.rs");
+
+/// The files of a new day, relative to its directory
+fn files(day: &str) -> Vec<(&'static str, String)> {
+    [
+        ("Cargo.toml", CARGO_TEMPLATE),
+        ("src/main.rs", MAIN_TEMPLATE),
+        ("src/lib.rs", LIB_TEMPLATE),
+        ("benches/{{day}}.rs", BENCH_TEMPLATE),
+        ("fixtures/example.txt", ""),
+        ("fixtures/example.answers", "-\n-\n"),
+    ]
+    .into_iter()
+    .map(|(path, template)| (path, template.replace("{{day}}", day)))
+    .collect()
+}
+
+/// Create `dN/` from the template, with empty example fixtures and a benchmark, and add it to
+/// the workspace. The workspace manifest is written last, so that it never lists a crate that
+/// couldn't be written.
+pub fn new_day(root: &Path, day: &str) -> Result<(), String> {
+    let number = day
+        .strip_prefix('d')
+        .and_then(|n| n.parse::<u32>().ok())
+        .ok_or(format!("Invalid day: {}", day))?;
+    let dir = root.join(day);
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()));
+    }
+    let manifest_path = root.join("Cargo.toml");
+    let manifest = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Could not read {}: {}", manifest_path.display(), e))?;
+    let manifest = with_member(&manifest, day, number)?;
+    for (path, contents) in files(day) {
+        let path = dir.join(path.replace("{{day}}", day));
+        let written = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| std::fs::write(&path, contents));
+        if let Err(e) = written {
+            // Leave nothing half made behind
+            if dir.is_dir() {
+                let _ = std::fs::remove_dir_all(&dir);
+            }
+            return Err(format!("Could not write {}: {}", path.display(), e));
+        }
+    }
+    std::fs::write(&manifest_path, manifest)
+        .map_err(|e| format!("Could not write {}: {}", manifest_path.display(), e))
+}
+
+/// The workspace manifest with the day inserted into the members right after the previous day
+fn with_member(manifest: &str, day: &str, number: u32) -> Result<String, String> {
+    let mut lines = manifest.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+    let start = lines
+        .iter()
+        .position(|l| l.trim() == "members = [")
+        .ok_or("No workspace members in Cargo.toml")?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|l| l.trim() == "]")
+            .ok_or("Unclosed workspace members in Cargo.toml")?;
+    let member_day = |l: &str| {
+        l.trim()
+            .trim_end_matches(',')
+            .trim_matches('"')
+            .strip_prefix('d')
+            .and_then(|n| n.parse::<u32>().ok())
+    };
+    // Before the first later day, or else after the last day, or else at the end
+    let (mut later, mut after_last) = (None, None);
+    for (i, line) in lines.iter().enumerate().take(end).skip(start + 1) {
+        match member_day(line) {
+            Some(n) if n == number => return Err(format!("{} is already a member", day)),
+            Some(n) if n > number => later = later.or(Some(i)),
+            Some(_) => after_last = Some(i + 1),
+            None => {}
+        }
+    }
+    let insert_at = later.or(after_last).unwrap_or(end);
+    lines.insert(insert_at, format!("    \"{}\",", day));
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const MANIFEST: &str = "[workspace]\nmembers = [\n    \"aoc\",\n    \"d1\",\n    \"d2\",\n    \"d10\",\n    \"interval\",\n]\n";
+
+    /// A workspace root of its own for each test, with `manifest` as its Cargo.toml
+    fn workspace(name: &str, manifest: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("aoc-new-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("Cargo.toml"), manifest).unwrap();
+        root
+    }
+
+    fn members(manifest: &str) -> Vec<String> {
+        manifest
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix('"'))
+            .map(|l| l.trim_end_matches("\",").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn with_member_keeps_days_in_order() {
+        let mut manifest = MANIFEST.to_string();
+        for (day, number) in [("d3", 3), ("d22", 22), ("d0", 0)] {
+            manifest = with_member(&manifest, day, number).unwrap();
+        }
+        assert_eq!(
+            members(&manifest),
+            ["aoc", "d0", "d1", "d2", "d3", "d10", "d22", "interval"]
+        );
+    }
+
+    #[test]
+    fn with_member_errors() {
+        assert_eq!(
+            with_member(MANIFEST, "d2", 2),
+            Err("d2 is already a member".to_string())
+        );
+        assert!(with_member("[package]\n", "d3", 3).is_err());
+        assert!(with_member("[workspace]\nmembers = [\n", "d3", 3).is_err());
+    }
+
+    #[test]
+    fn new_day_leaves_the_workspace_alone_on_errors() {
+        let root = workspace("unchanged", MANIFEST);
+        // Already a member, so no crate is written either
+        assert!(new_day(&root, "d2").is_err());
+        assert!(!root.join("d2").exists());
+        // A dangling link where the crate should go makes writing its files fail
+        std::os::unix::fs::symlink(root.join("missing"), root.join("d22")).unwrap();
+        let error = new_day(&root, "d22").unwrap_err();
+        assert!(error.starts_with("Could not write"), "{}", error);
+        assert_eq!(
+            std::fs::read_to_string(root.join("Cargo.toml")).unwrap(),
+            MANIFEST
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn new_day_writes_every_file() {
+        let root = workspace("files", MANIFEST);
+        new_day(&root, "d22").unwrap();
+        for (path, contents) in files("d22") {
+            assert!(!contents.contains("{{"), "{} has a placeholder left", path);
+            let path = root.join("d22").join(path.replace("{{day}}", "d22"));
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+        }
+        assert!(root.join("d22/benches/d22.rs").exists());
+        let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(members(&manifest).contains(&"d22".to_string()));
+        assert!(new_day(&root, "d22").is_err());
+        assert!(new_day(&root, "day22").is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use crate::run::DayRun;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+code { background: #f4f4f4; padding: 0 0.2em; }
+figure { display: inline-block; margin: 0.5em; vertical-align: top; }
+figure svg { width: 24em; height: 24em; border: 1px solid #ccc; }
+.error { color: #b00; white-space: pre-wrap; }
+";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn phase_time(run: &DayRun, name: &str) -> String {
+    run.phases
+        .iter()
+        .find(|p| p.name == name)
+        .map(|p| p.busy.clone())
+        .unwrap_or("-".to_string())
+}
+
+fn summary(results: &[(String, Result<DayRun, String>)]) -> String {
+    let rows = results
+        .iter()
+        .map(|(day, result)| match result {
+            Ok(run) => format!(
+                "<tr><td><a href=\"#{day}\">{day}</a></td><td>{answers}</td><td class=\"num\">{total:.2}ms</td><td class=\"num\">{parse}</td><td class=\"num\">{lines}</td><td class=\"num\">{bytes}</td></tr>",
+                day = run.day,
+                answers = run
+                    .answers
+                    .iter()
+                    .map(|a| format!("<code>{}</code>", escape(a)))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                total = run.elapsed_ms,
+                parse = phase_time(run, "parse"),
+                lines = run.input_lines,
+                bytes = run.input_bytes,
+            ),
+            Err(e) => format!(
+                "<tr><td>{}</td><td colspan=\"5\" class=\"error\">{}</td></tr>",
+                day,
+                escape(e)
+            ),
+        })
+        .collect::<String>();
+    format!(
+        "<table><tr><th>Day</th><th>Answers</th><th>Total</th><th>Parse</th><th>Input lines</th><th>Input bytes</th></tr>{}</table>",
+        rows
+    )
+}
+
+fn day_section(run: &DayRun) -> String {
+    let phases = run
+        .phases
+        .iter()
+        .map(|phase| {
+            let counters = run
+                .counters
+                .iter()
+                .filter(|c| c.phase.as_deref() == Some(phase.name.as_str()))
+                .map(|c| format!("{}={}", escape(&c.name), c.value))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                "<tr><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                escape(&phase.name),
+                escape(&phase.busy),
+                counters
+            )
+        })
+        .collect::<String>();
+    let figures = run
+        .visualisations
+        .iter()
+        .map(|v| {
+            let caption = match &v.phase {
+                Some(phase) => format!("{} ({})", v.name, phase),
+                None => v.name.clone(),
+            };
+            format!(
+                "<figure>{}<figcaption>{}</figcaption></figure>",
+                v.svg,
+                escape(&caption)
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<section id=\"{day}\"><h2>{day}</h2><table><tr><th>Phase</th><th>Time</th><th>Counters</th></tr>{phases}</table>{figures}</section>",
+        day = run.day,
+    )
+}
+
+/// A standalone HTML page for a run over several days, with the SVGs inlined
+pub fn render(results: &[(String, Result<DayRun, String>)]) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Advent of Code 2023</title><style>{}</style></head><body><h1>Advent of Code 2023</h1>{}{}</body></html>\n",
+        STYLE,
+        summary(results),
+        results
+            .iter()
+            .filter_map(|(_, result)| result.as_ref().ok())
+            .map(day_section)
+            .collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::{CounterValue, Phase, Visualisation};
+
+    fn run() -> DayRun {
+        DayRun {
+            day: "d3".to_string(),
+            answers: vec!["4361".to_string(), "<&>".to_string()],
+            elapsed_ms: 1.5,
+            input_lines: 10,
+            input_bytes: 110,
+            phases: vec![Phase {
+                name: "parse".to_string(),
+                busy: "80µs".to_string(),
+            }],
+            counters: vec![CounterValue {
+                phase: Some("parse".to_string()),
+                name: "a<b".to_string(),
+                value: 7,
+            }],
+            visualisations: vec![Visualisation {
+                phase: Some("part1".to_string()),
+                name: "grid & parts".to_string(),
+                svg: "<svg viewBox=\"0 0 1 1\"><rect/></svg>".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn successful_run() {
+        let page = render(&[("d3".to_string(), Ok(run()))]);
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<td><a href=\"#d3\">d3</a></td><td><code>4361</code> <code>&lt;&amp;&gt;</code></td><td class=\"num\">1.50ms</td><td class=\"num\">80µs</td>"));
+        assert!(
+            page.contains("<tr><td>parse</td><td class=\"num\">80µs</td><td>a&lt;b=7</td></tr>")
+        );
+        // The picture goes in as it is, so it shows without any file next to the page
+        assert!(page.contains("<figure><svg viewBox=\"0 0 1 1\"><rect/></svg><figcaption>grid &amp; parts (part1)</figcaption></figure>"));
+        assert!(page.contains("<section id=\"d3\">"));
+    }
+
+    #[test]
+    fn failed_run() {
+        let results = [
+            ("d3".to_string(), Ok(run())),
+            (
+                "d4".to_string(),
+                Err("thread 'main' panicked at <input>\n& more".to_string()),
+            ),
+        ];
+        let page = render(&results);
+        assert!(page.contains("<tr><td>d4</td><td colspan=\"5\" class=\"error\">thread 'main' panicked at &lt;input&gt;\n&amp; more</td></tr>"));
+        assert!(!page.contains("<section id=\"d4\">"));
+        assert_eq!(page.matches("<section").count(), 1);
+    }
+}
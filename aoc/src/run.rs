@@ -0,0 +1,273 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Phase {
+    pub name: String,
+    pub busy: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CounterValue {
+    pub phase: Option<String>,
+    pub name: String,
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Visualisation {
+    pub phase: Option<String>,
+    pub name: String,
+    pub svg: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayRun {
+    pub day: String,
+    pub answers: Vec<String>,
+    pub elapsed_ms: f64,
+    pub input_lines: usize,
+    pub input_bytes: usize,
+    pub phases: Vec<Phase>,
+    pub counters: Vec<CounterValue>,
+    pub visualisations: Vec<Visualisation>,
+}
+
+/// What a day logged as JSON lines on stderr
+#[derive(Debug, Clone, Default)]
+pub struct Events {
+    pub phases: Vec<Phase>,
+    pub counters: Vec<CounterValue>,
+    pub visualisations: Vec<Visualisation>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Ask the days for their SVG visualisations
+    pub visualise: bool,
+    /// Have the days run all their strategies against each other
+    pub cross_check: bool,
+}
+
+pub fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn target_dir(root: &Path) -> PathBuf {
+    match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => root.join("target"),
+    }
+}
+
+/// Accepts both `17` and `d17`
+pub fn normalize_day(day: &str) -> String {
+    match day.strip_prefix('d') {
+        Some(_) => day.to_string(),
+        None => format!("d{}", day),
+    }
+}
+
+/// All day crates in the workspace, or the selected ones, in numeric order
+pub fn find_days(root: &Path, selected: &[String]) -> Vec<String> {
+    let mut days = std::fs::read_dir(root)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            name.strip_prefix('d')
+                .is_some_and(|n| n.parse::<u32>().is_ok())
+                && root.join(name).join("Cargo.toml").exists()
+        })
+        .collect::<Vec<_>>();
+    if !selected.is_empty() {
+        let selected = selected
+            .iter()
+            .map(|d| normalize_day(d))
+            .collect::<Vec<_>>();
+        days.retain(|d| selected.contains(d));
+    }
+    days.sort_by_key(|d| d[1..].parse::<u32>().unwrap());
+    days
+}
+
+pub fn build(root: &Path, days: &[String]) {
+    let mut cmd = Command::new(std::env::var("CARGO").unwrap_or("cargo".to_string()));
+    cmd.current_dir(root)
+        .args(["build", "--release", "--quiet"]);
+    days.iter().for_each(|d| {
+        cmd.args(["-p", d]);
+    });
+    let status = cmd.status().unwrap();
+    assert!(status.success(), "Failed to build {:?}", days);
+}
+
+/// Run a day binary on its `input.txt` and collect the events it logged
+pub fn run_day(root: &Path, day: &str, options: &RunOptions) -> Result<DayRun, String> {
+    let dir = root.join(day);
+    if !dir.join("input.txt").exists() {
+        return Err("no input.txt".to_string());
+    }
+    run_in(root, day, &dir, options)
+}
+
+/// The day binary set up to run with `dir` as working directory and log JSON events
+pub fn command(root: &Path, day: &str, dir: &Path, options: &RunOptions) -> Command {
+    let mut cmd = Command::new(target_dir(root).join("release").join(day));
+    cmd.current_dir(dir)
+        .env("RUST_LOG", "info")
+        .env("AOC_LOG_FORMAT", "json");
+    if options.visualise {
+        cmd.env("AOC_VISUALISE", "1");
+    }
+    if options.cross_check {
+        cmd.arg("--cross-check");
+    }
+    cmd
+}
+
+/// What a failed day printed on stderr, without its JSON events
+pub fn error_message(stderr: &str) -> String {
+    stderr
+        .lines()
+        .filter(|l| serde_json::from_str::<Value>(l).is_err())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Run a day binary with `dir` as working directory, which must hold an `input.txt`
+pub fn run_in(root: &Path, day: &str, dir: &Path, options: &RunOptions) -> Result<DayRun, String> {
+    let input = std::fs::read_to_string(dir.join("input.txt")).map_err(|e| e.to_string())?;
+    let mut cmd = command(root, day, dir, options);
+    let start = Instant::now();
+    let output = cmd.output().map_err(|e| e.to_string())?;
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1e3;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(error_message(&stderr));
+    }
+    let events = parse_events(&stderr);
+    Ok(DayRun {
+        day: day.to_string(),
+        answers: String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|s| s.to_string())
+            .collect(),
+        elapsed_ms,
+        input_lines: input.lines().count(),
+        input_bytes: input.len(),
+        phases: events.phases,
+        counters: events.counters,
+        visualisations: events.visualisations,
+    })
+}
+
+/// Phase timings from span close events, counters and visualisations, skipping the lines
+/// that aren't JSON
+pub fn parse_events(stderr: &str) -> Events {
+    let mut events = Events::default();
+    for event in stderr
+        .lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+    {
+        let span = event["span"]["name"].as_str().map(|s| s.to_string());
+        let fields = &event["fields"];
+        if event["target"] == common::COUNTER_TARGET {
+            events.counters.push(CounterValue {
+                phase: span,
+                name: fields["counter"].as_str().unwrap_or_default().to_string(),
+                value: fields["value"].as_u64().unwrap_or_default(),
+            });
+        } else if event["target"] == common::VISUALISATION_TARGET {
+            events.visualisations.push(Visualisation {
+                phase: span,
+                name: fields["name"].as_str().unwrap_or_default().to_string(),
+                svg: fields["svg"].as_str().unwrap_or_default().to_string(),
+            });
+        } else if fields["message"] == "close" {
+            if let (Some(name), Some(busy)) = (span, fields["time.busy"].as_str()) {
+                events.phases.push(Phase {
+                    name,
+                    busy: busy.to_string(),
+                });
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    static STEPS: common::Counter = common::Counter::new("steps");
+
+    /// What a day logs with `AOC_LOG_FORMAT=json`, as set up by `common::init_tracing`
+    fn logged(run: impl FnOnce()) -> String {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(move || writer.clone())
+            .json()
+            .finish();
+        tracing::subscriber::with_default(subscriber, run);
+        let bytes = buffer.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn events() {
+        let stderr = logged(|| {
+            tracing::info_span!("parse").in_scope(|| {});
+            tracing::info_span!("part1").in_scope(|| {
+                STEPS.add(41);
+                STEPS.incr();
+                STEPS.report();
+                tracing::info!("not a counter");
+            });
+            STEPS.add(7);
+            STEPS.report();
+        });
+        let events = parse_events(&format!("thread 'main' panicked\n{}", stderr));
+        let phases = events
+            .phases
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(phases, ["parse", "part1"]);
+        assert!(events.phases.iter().all(|p| !p.busy.is_empty()));
+        let counters = events
+            .counters
+            .iter()
+            .map(|c| (c.phase.as_deref(), c.name.as_str(), c.value))
+            .collect::<Vec<_>>();
+        assert_eq!(counters, [(Some("part1"), "steps", 42), (None, "steps", 7)]);
+        assert!(events.visualisations.is_empty());
+    }
+}
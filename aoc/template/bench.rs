@@ -0,0 +1,16 @@
+use common::bench::bench;
+use std::path::Path;
+use {{day}}::{parse_input, part1, part2};
+
+fn main() {
+    // The real input when there is one, the example otherwise
+    let file = match Path::new("input.txt").exists() {
+        true => "input.txt",
+        false => "fixtures/example.txt",
+    };
+    let lines = common::read_lines(file);
+    bench("parse", || parse_input(&lines));
+    let input = parse_input(&lines);
+    bench("part1", || part1(&input));
+    bench("part2", || part2(&input));
+}
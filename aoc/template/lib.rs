@@ -0,0 +1,11 @@
+pub fn parse_input(lines: &[String]) -> Vec<String> {
+    lines.to_vec()
+}
+
+pub fn part1(input: &[String]) -> u64 {
+    todo!("part 1 of {{day}} for {} lines", input.len())
+}
+
+pub fn part2(input: &[String]) -> u64 {
+    todo!("part 2 of {{day}} for {} lines", input.len())
+}
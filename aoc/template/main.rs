@@ -0,0 +1,13 @@
+use tracing::info_span;
+use {{day}}::{parse_input, part1, part2};
+
+fn main() {
+    common::init_tracing();
+    let input = info_span!("parse").in_scope(|| parse_input(&common::read_lines("input.txt")));
+    info_span!("part1").in_scope(|| {
+        println!("{}", part1(&input));
+    });
+    info_span!("part2").in_scope(|| {
+        println!("{}", part2(&input));
+    });
+}
@@ -0,0 +1,19 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Time `f` over about a second of runs and print the mean, for `cargo bench` targets with
+/// `harness = false`
+pub fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    // A first run sizes the batch, so slow parts aren't run thousands of times
+    let start = Instant::now();
+    black_box(f());
+    let once = start.elapsed().as_nanos().max(1);
+    let runs = (Duration::from_secs(1).as_nanos() / once).clamp(1, 1_000_000) as u32;
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(f());
+    }
+    let mean = start.elapsed() / runs;
+    println!("{:<8}{:>14.3?}  ({} runs)", name, mean, runs);
+    mean
+}
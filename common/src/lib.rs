@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+pub mod bench;
+pub mod model;
+pub mod strategy;
+
+/// Target used for counter events, so the runner can tell them apart from other logs
+pub const COUNTER_TARGET: &str = "counters";
+
+/// Target used for the SVG pictures emitted by `visualise`
+pub const VISUALISATION_TARGET: &str = "visualisations";
+
+/// The lines of a day's input
+pub fn read_lines(file_name: &str) -> Vec<String> {
+    std::fs::read_to_string(file_name)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", file_name, e))
+        .lines()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Value following `name` on the command line, if the flag is there
+pub fn flag_value(name: &str) -> Option<String> {
+    let args = std::env::args().collect::<Vec<_>>();
+    args.iter().position(|a| a == name).map(|i| {
+        args.get(i + 1)
+            .unwrap_or_else(|| panic!("Missing value for {}", name))
+            .clone()
+    })
+}
+
+pub fn has_flag(name: &str) -> bool {
+    std::env::args().any(|a| a == name)
+}
+
+/// Install the global subscriber for a day binary.
+///
+/// Everything is written to stderr so stdout only carries the answers.
+/// The filter comes from `RUST_LOG` (default `warn`), and setting
+/// `AOC_LOG_FORMAT=json` switches to one JSON object per line.
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr);
+    match std::env::var("AOC_LOG_FORMAT").as_deref() {
+        Ok("json") => builder.json().init(),
+        _ => builder.init(),
+    }
+}
+
+/// Emit an SVG picture in the current span for the runner's report.
+///
+/// `render` is only called when `AOC_VISUALISE` is set, as the pictures can be
+/// expensive to build and too large to log on every run.
+pub fn visualise(name: &str, render: impl FnOnce() -> String) {
+    if std::env::var_os("AOC_VISUALISE").is_some() {
+        let svg = render();
+        tracing::info!(target: VISUALISATION_TARGET, name, svg);
+    }
+}
+
+/// Global counter for internal search effort (heap pops, expansions, ...)
+pub struct Counter {
+    name: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str) -> Counter {
+        Counter {
+            name,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn incr(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.value.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Emit the current value as an event in the current span and reset it
+    pub fn report(&self) -> u64 {
+        let value = self.value.swap(0, Ordering::Relaxed);
+        tracing::info!(target: COUNTER_TARGET, counter = self.name, value);
+        value
+    }
+}
@@ -0,0 +1,186 @@
+use clap::{Parser, ValueEnum};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum Format {
+    Json,
+    Ron,
+}
+
+impl Format {
+    /// RON for `.ron` files, JSON for anything else
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => Format::Ron,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// The model flags, shared by every day that has a model
+#[derive(Debug, Default, PartialEq, Eq, Parser)]
+#[command(no_binary_name = true, disable_help_flag = true)]
+pub struct ModelArgs {
+    /// Read a model dumped earlier (`.json` or `.ron`) instead of parsing the input
+    #[arg(long)]
+    pub load: Option<PathBuf>,
+    /// Print the model instead of solving the puzzle
+    #[arg(long, value_enum)]
+    pub dump: Option<Format>,
+}
+
+impl ModelArgs {
+    /// From a whole command line, skipping the flags of the day itself
+    pub fn parse_args(args: &[String]) -> Result<ModelArgs, String> {
+        // The days read their other flags themselves, so only ours go to clap
+        let mut ours = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            if args[i] == "--load" || args[i] == "--dump" {
+                ours.extend(args[i..].iter().take(2).cloned());
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+        ModelArgs::try_parse_from(ours).map_err(|e| e.to_string().trim_end().to_string())
+    }
+}
+
+/// What `load_or_parse` ended up with
+#[derive(Debug, PartialEq, Eq)]
+pub enum Loaded<T> {
+    Model(T),
+    /// `--dump` was given: the model written out, for `main` to print
+    Dump(String),
+}
+
+pub fn dump<T: Serialize>(model: &T, format: Format) -> Result<String, String> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(model).map_err(|e| e.to_string()),
+        Format::Ron => ron::ser::to_string_pretty(model, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Could not write the model as {:?}: {}", format, e))
+}
+
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    from_str(&contents, Format::from_path(path))
+        .map_err(|e| format!("Could not load a model from {}: {}", path.display(), e))
+}
+
+/// Read a model written by `dump`
+pub fn from_str<T: DeserializeOwned>(text: &str, format: Format) -> Result<T, String> {
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        Format::Ron => ron::from_str(text).map_err(|e| e.to_string()),
+    }
+}
+
+/// Get the parsed input model of a day, honouring the model flags on the command line.
+///
+/// `--load <file>` reads a model dumped earlier (`.json` or `.ron`) instead of
+/// calling `parse`. `--dump <json|ron>` gives back the model as text, which `main`
+/// should print instead of solving.
+pub fn load_or_parse<T: Serialize + DeserializeOwned>(
+    parse: impl FnOnce() -> T,
+) -> Result<Loaded<T>, String> {
+    try_load_or_parse(|| Ok(parse()))
+}
+
+/// `load_or_parse` for a parser that can fail, with its error passed on
+pub fn try_load_or_parse<T: Serialize + DeserializeOwned>(
+    parse: impl FnOnce() -> Result<T, String>,
+) -> Result<Loaded<T>, String> {
+    let args = ModelArgs::parse_args(&std::env::args().skip(1).collect::<Vec<_>>())?;
+    let model = match args.load {
+        Some(path) => load(&path)?,
+        None => parse()?,
+    };
+    match args.dump {
+        Some(format) => Ok(Loaded::Dump(dump(&model, format)?)),
+        None => Ok(Loaded::Model(model)),
+    }
+}
+
+/// `load_or_parse` for `main`: the model, or else the dump printed or the error reported on
+/// stderr, and the process ended
+pub fn load_or_exit<T: Serialize + DeserializeOwned>(parse: impl FnOnce() -> T) -> T {
+    model_or_exit(load_or_parse(parse))
+}
+
+/// `try_load_or_parse` for `main`, as `load_or_exit`
+pub fn try_load_or_exit<T: Serialize + DeserializeOwned>(
+    parse: impl FnOnce() -> Result<T, String>,
+) -> T {
+    model_or_exit(try_load_or_parse(parse))
+}
+
+fn model_or_exit<T>(loaded: Result<Loaded<T>, String>) -> T {
+    match loaded {
+        Ok(Loaded::Model(model)) => model,
+        Ok(Loaded::Dump(text)) => {
+            println!("{}", text);
+            std::process::exit(0);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn model_args_skip_other_flags() {
+        assert_eq!(
+            ModelArgs::parse_args(&args("--strategy binary --dump ron --cross-check")),
+            Ok(ModelArgs {
+                load: None,
+                dump: Some(Format::Ron),
+            })
+        );
+        assert_eq!(
+            ModelArgs::parse_args(&args("--load model.json --trace")),
+            Ok(ModelArgs {
+                load: Some(PathBuf::from("model.json")),
+                dump: None,
+            })
+        );
+        assert_eq!(ModelArgs::parse_args(&args("")), Ok(ModelArgs::default()));
+    }
+
+    #[test]
+    fn model_args_errors() {
+        assert!(ModelArgs::parse_args(&args("--dump yaml")).is_err());
+        assert!(ModelArgs::parse_args(&args("--dump")).is_err());
+    }
+
+    #[test]
+    fn load_reports_the_path() {
+        let error = load::<Vec<u64>>(Path::new("/no/such/model.json")).unwrap_err();
+        assert!(error.contains("/no/such/model.json"), "{}", error);
+    }
+
+    #[test]
+    fn dump_and_load_round_trip() {
+        let model = vec![(1_u64, "a".to_string()), (2, "b".to_string())];
+        for (format, name) in [(Format::Json, "model.json"), (Format::Ron, "model.ron")] {
+            let path = std::env::temp_dir().join(format!("common-{}-{}", std::process::id(), name));
+            std::fs::write(&path, dump(&model, format).unwrap()).unwrap();
+            assert_eq!(load::<Vec<(u64, String)>>(&path), Ok(model.clone()));
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use tracing::{debug, debug_span};
+
+/// Compute an answer with one of several algorithms, picked on the command line.
+///
+/// `--strategy <name>` runs the named algorithm (the first one by default) and
+/// `--strategy list` prints the available names and exits. `--cross-check` runs
+/// all of them, in a `strategy` debug span each, and panics if they disagree.
+pub fn choose<T: PartialEq + Debug>(strategies: &[(&str, &dyn Fn() -> T)]) -> T {
+    let cross_check = crate::has_flag("--cross-check");
+    let name = crate::flag_value("--strategy");
+    if !cross_check && name.as_deref() == Some("list") {
+        let names = strategies.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        println!("{}", names.join("\n"));
+        std::process::exit(0);
+    }
+    pick(cross_check, name.as_deref(), strategies)
+}
+
+/// `choose` once the flags are read
+fn pick<T: PartialEq + Debug>(
+    cross_check: bool,
+    name: Option<&str>,
+    strategies: &[(&str, &dyn Fn() -> T)],
+) -> T {
+    if cross_check {
+        let mut results = strategies
+            .iter()
+            .map(|(name, strategy)| {
+                let result = debug_span!("strategy", name).in_scope(strategy);
+                debug!(strategy = name, ?result);
+                (name, result)
+            })
+            .collect::<Vec<_>>();
+        if results.iter().any(|(_, result)| *result != results[0].1) {
+            let results = results
+                .iter()
+                .map(|(name, result)| format!("{}: {:?}", name, result))
+                .collect::<Vec<_>>();
+            panic!("Strategies disagree: {}", results.join(", "));
+        }
+        return results.swap_remove(0).1;
+    }
+    match name {
+        None => strategies[0].1(),
+        Some(name) => match strategies.iter().find(|(n, _)| *n == name) {
+            Some((_, strategy)) => strategy(),
+            None => {
+                let names = strategies.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                panic!(
+                    "Unknown strategy: {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn picks_by_name() {
+        let strategies: [(&str, &dyn Fn() -> u32); 2] = [("fast", &|| 1), ("slow", &|| 2)];
+        assert_eq!(pick(false, None, &strategies), 1);
+        assert_eq!(pick(false, Some("slow"), &strategies), 2);
+    }
+
+    #[test]
+    fn cross_check_runs_every_strategy() {
+        let runs = Cell::new(0);
+        let count = || {
+            runs.set(runs.get() + 1);
+            7
+        };
+        let strategies: [(&str, &dyn Fn() -> u32); 3] =
+            [("a", &count), ("b", &count), ("c", &|| 7)];
+        assert_eq!(pick(true, Some("c"), &strategies), 7);
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Strategies disagree: a: 1, b: 1, c: 2")]
+    fn cross_check_disagreement() {
+        let strategies: [(&str, &dyn Fn() -> u32); 3] = [("a", &|| 1), ("b", &|| 1), ("c", &|| 2)];
+        pick(true, None, &strategies);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown strategy: medium, expected one of fast, slow")]
+    fn unknown_strategy() {
+        let strategies: [(&str, &dyn Fn() -> u32); 2] = [("fast", &|| 1), ("slow", &|| 2)];
+        pick(false, Some("medium"), &strategies);
+    }
+}
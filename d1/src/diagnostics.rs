@@ -0,0 +1,99 @@
+use crate::rules::{Calibration, Invalid};
+use crate::scanner::Match;
+
+/// What both parts make of one line of the calibration document
+#[derive(Debug, Clone)]
+pub struct LineReport {
+    /// Starting at 1, as in an editor
+    pub number: usize,
+    pub part1: Result<Calibration, Invalid>,
+    pub part2: Result<Calibration, Invalid>,
+}
+
+fn tokens(tokens: &[Match]) -> String {
+    tokens
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn digits(tokens: &[Match]) -> String {
+    tokens.iter().map(|t| t.value.to_string()).collect()
+}
+
+fn describe(calibration: &Result<Calibration, Invalid>) -> String {
+    match calibration {
+        Ok(c) => format!(
+            "{} from {} and {}",
+            c.value,
+            tokens(&c.first),
+            tokens(&c.last)
+        ),
+        Err(invalid) => format!("error: {}", invalid),
+    }
+}
+
+fn list(numbers: &[usize]) -> String {
+    match numbers.is_empty() {
+        true => "none".to_string(),
+        false => numbers
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// One line per input line, then the lines that failed and the ones where the parts disagree.
+/// The positions are byte offsets in the line.
+pub fn render(reports: &[LineReport]) -> String {
+    let mut result = String::new();
+    let mut failed = (Vec::new(), Vec::new());
+    let mut differing = Vec::new();
+    for report in reports {
+        let difference = match (&report.part1, &report.part2) {
+            (Ok(p1), Ok(p2)) if p1.value != p2.value => {
+                differing.push(report.number);
+                let mut changes = Vec::new();
+                let (first, last) = (
+                    (digits(&p1.first), digits(&p2.first)),
+                    (digits(&p1.last), digits(&p2.last)),
+                );
+                if first.0 != first.1 {
+                    changes.push(format!("first {} -> {}", first.0, first.1));
+                }
+                if last.0 != last.1 {
+                    changes.push(format!("last {} -> {}", last.0, last.1));
+                }
+                format!(
+                    "; part 2 {:+} ({})",
+                    p2.value as i128 - p1.value as i128,
+                    changes.join(", ")
+                )
+            }
+            _ => String::new(),
+        };
+        if report.part1.is_err() {
+            failed.0.push(report.number);
+        }
+        if report.part2.is_err() {
+            failed.1.push(report.number);
+        }
+        result += &format!(
+            "line {}: part 1 {}; part 2 {}{}\n",
+            report.number,
+            describe(&report.part1),
+            describe(&report.part2),
+            difference
+        );
+    }
+    result += &format!(
+        "{} lines\npart 1 errors on lines: {}\npart 2 errors on lines: {}\nparts differ on lines: {}\n",
+        reports.len(),
+        list(&failed.0),
+        list(&failed.1),
+        list(&differing)
+    );
+    result
+}
@@ -0,0 +1,83 @@
+/// Which characters count as digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Digits {
+    /// `0` to `9` only, as in the puzzle
+    Ascii,
+    /// Any decimal digit (Unicode category Nd), e.g. full-width `５` or Devanagari `५`
+    Unicode,
+}
+
+/// Code points of the zero of every run of ten decimal digits (general category Nd) in
+/// Unicode 15.1, from its `UnicodeData.txt`. Scripts added later, like the Garay digits of
+/// Unicode 16, are not counted.
+const DECIMAL_ZEROS: [u32; 68] = [
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x11F50, 0x16A60,
+    0x16AC0, 0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0,
+    0x1E950, 0x1FBF0,
+];
+
+impl Digits {
+    pub fn value(self, ch: char) -> Option<u32> {
+        match self {
+            Digits::Ascii => ch.to_digit(10),
+            Digits::Unicode => {
+                let code = ch as u32;
+                DECIMAL_ZEROS
+                    .iter()
+                    .find(|&&zero| code >= zero && code < zero + 10)
+                    .map(|zero| code - zero)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zeros_start_runs_of_ten_digits() {
+        assert!(DECIMAL_ZEROS.windows(2).all(|w| w[0] + 10 <= w[1]));
+        for zero in DECIMAL_ZEROS {
+            for value in 0..10 {
+                let ch = char::from_u32(zero + value).unwrap();
+                assert!(ch.is_numeric(), "{:?} is not numeric", ch);
+                assert_eq!(Digits::Unicode.value(ch), Some(value), "{:?}", ch);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_agrees_with_to_digit() {
+        for ch in (0..128).filter_map(char::from_u32) {
+            assert_eq!(Digits::Unicode.value(ch), ch.to_digit(10), "{:?}", ch);
+            assert_eq!(Digits::Ascii.value(ch), ch.to_digit(10), "{:?}", ch);
+        }
+    }
+
+    #[test]
+    fn scripts() {
+        for (ch, value) in [
+            ('٣', Some(3)),
+            ('۷', Some(7)),
+            ('५', Some(5)),
+            ('๙', Some(9)),
+            ('５', Some(5)),
+            ('𝟗', Some(9)),
+            ('𝟶', Some(0)),
+            // Numeric, but not decimal digits
+            ('²', None),
+            ('½', None),
+            ('Ⅻ', None),
+            ('①', None),
+            ('〇', None),
+        ] {
+            assert_eq!(Digits::Unicode.value(ch), value, "{:?}", ch);
+        }
+        assert_eq!(Digits::Ascii.value('５'), None);
+    }
+}
@@ -1,7 +1,15 @@
+mod diagnostics;
+mod digits;
+mod rules;
+mod scanner;
+
+use diagnostics::LineReport;
+use digits::Digits;
+use rules::{Calibration, Invalid, Rule};
+use scanner::{Match, Origin, Scanner, Vocabulary};
 use std::fs::File;
 use std::io::Read;
-use std::str::FromStr;
-use strum::EnumString;
+use tracing::{debug, info_span};
 
 fn read_file(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -10,87 +18,271 @@ fn read_file(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-fn parse_line(line: &str) -> u64 {
-    let digits = line.chars().filter(|c| c.is_digit(10)).collect::<String>();
-    let first = digits.chars().next().unwrap();
-    let last = digits.chars().last().unwrap();
-    let value = (first.to_string() + &last.to_string())
-        .parse::<u64>()
-        .unwrap();
-    value
+fn digit_tokens(line: &str, digits: Digits) -> Vec<Match> {
+    line.char_indices()
+        .filter_map(|(i, ch)| {
+            digits.value(ch).map(|value| Match {
+                start: i,
+                end: i + ch.len_utf8(),
+                value,
+                origin: Origin::Digit,
+            })
+        })
+        .collect()
 }
 
-#[derive(Debug, PartialEq, EnumString)]
-#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
-enum StringNumber {
-    One = 1,
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    Seven = 7,
-    Eight = 8,
-    Nine = 9,
+fn parse_line(line: &str, digits: Digits, rule: &Rule) -> Result<Calibration, Invalid> {
+    rule.apply(&digit_tokens(line, digits))
 }
 
-fn parse_line_2(line: &str) -> u64 {
-    let try_parse = |s: String| match StringNumber::from_str(s.as_str()) {
-        Ok(v) => Some(v as u64),
-        Err(_) => None,
-    };
-    let digits = line
-        .char_indices()
-        .map(|(i, ch)| match ch.is_digit(10) {
-            true => Some(ch.to_string()),
-            false => {
-                let r3 = if line.len() >= i + 3 {
-                    try_parse(line[i..i + 3].to_string())
-                } else {
-                    None as Option<u64>
-                };
-                let r4 = if line.len() >= i + 4 {
-                    try_parse(line[i..i + 4].to_string())
-                } else {
-                    None as Option<u64>
-                };
-                let r5 = if line.len() >= i + 5 {
-                    try_parse(line[i..i + 5].to_string())
-                } else {
-                    None as Option<u64>
-                };
-
-                match r5 {
-                    Some(v) => Some(v.to_string()),
-                    None => match r4 {
-                        Some(v) => Some(v.to_string()),
-                        None => match r3 {
-                            Some(v) => Some(v.to_string()),
-                            None => None,
-                        },
-                    },
-                }
-            }
-        })
-        .filter(|s| s.is_some())
-        .map(|s| s.unwrap())
-        .collect::<String>();
-    let first = digits.chars().next().unwrap();
-    let last = digits.chars().last().unwrap();
-    let value = (first.to_string() + &last.to_string())
-        .parse::<u64>()
-        .unwrap();
-    value
+/// Digits and spelled out numbers of a line, in order
+fn tokens(line: &str, scanner: &Scanner, digits: Digits) -> Vec<Match> {
+    let mut tokens = digit_tokens(line, digits);
+    tokens.extend(scanner.find_overlapping(line));
+    tokens.sort_by_key(|m| (m.start, m.end));
+    tokens
+}
+
+fn parse_line_2(
+    line: &str,
+    scanner: &Scanner,
+    digits: Digits,
+    rule: &Rule,
+) -> Result<Calibration, Invalid> {
+    let tokens = rule.resolve_overlaps(tokens(line, scanner, digits));
+    debug!(line, ?tokens);
+    rule.apply(&tokens)
+}
+
+/// The sum of the calibration values, or what is wrong with the lines that have none
+fn sum_calibrations(
+    input: &[String],
+    parse: impl Fn(&str) -> Result<Calibration, Invalid>,
+) -> Result<u64, String> {
+    let mut sum = Some(0u64);
+    let mut errors = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        match parse(line) {
+            Ok(calibration) => sum = sum.and_then(|s| s.checked_add(calibration.value)),
+            Err(invalid) => errors.push(format!("line {}: {}", i + 1, invalid)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    sum.ok_or_else(|| "The sum of the calibration values overflows u64".to_string())
+}
+
+/// The answer line of a part, `-` when it has none, so the other part's answer stays on its
+/// own line
+fn answer(result: &Result<u64, String>) -> String {
+    match result {
+        Ok(sum) => sum.to_string(),
+        Err(_) => "-".to_string(),
+    }
+}
+
+/// Print the answer, or `-` and the errors on stderr. Whether it had an answer.
+fn report(part: usize, result: Result<u64, String>) -> bool {
+    println!("{}", answer(&result));
+    if let Err(error) = &result {
+        for line in error.lines() {
+            eprintln!("part {}: {}", part, line);
+        }
+    }
+    result.is_ok()
 }
 
 fn main() {
-    let input = read_file("input.txt");
-    println!(
-        "{:?}",
-        input.iter().map(|l| parse_line(l.as_str())).sum::<u64>()
-    );
-    println!(
-        "{:?}",
-        input.iter().map(|l| parse_line_2(l.as_str())).sum::<u64>()
-    );
+    common::init_tracing();
+    let input = info_span!("parse").in_scope(|| read_file("input.txt"));
+    // The puzzle only spells one to nine in English, `--vocabulary` takes other words
+    let vocabulary = match common::flag_value("--vocabulary") {
+        Some(spec) => Vocabulary::from_spec(&spec).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => Vocabulary::builtin("en").unwrap(),
+    };
+    let digits = match common::has_flag("--unicode-digits") {
+        true => Digits::Unicode,
+        false => Digits::Ascii,
+    };
+    let scanner = Scanner::new(&vocabulary);
+    let rule = Rule::from_args();
+    if common::has_flag("--diagnose") {
+        let reports = input
+            .iter()
+            .enumerate()
+            .map(|(i, l)| LineReport {
+                number: i + 1,
+                part1: parse_line(l, digits, &rule),
+                part2: parse_line_2(l, &scanner, digits, &rule),
+            })
+            .collect::<Vec<_>>();
+        print!("{}", diagnostics::render(&reports));
+        return;
+    }
+    // Both parts run even if one fails, as the example of part two has no answer for part one
+    let part1 = info_span!("part1").in_scope(|| {
+        report(
+            1,
+            sum_calibrations(&input, |l| parse_line(l, digits, &rule)),
+        )
+    });
+    let part2 = info_span!("part2").in_scope(|| {
+        report(
+            2,
+            sum_calibrations(&input, |l| parse_line_2(l, &scanner, digits, &rule)),
+        )
+    });
+    if !(part1 && part2) {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Combine, Pick};
+
+    const EXAMPLE: [&str; 4] = ["two1nine", "abc", "eightwothree", "7pqrstsixteen"];
+
+    fn reports(lines: &[&str]) -> Vec<LineReport> {
+        let scanner = Scanner::new(&Vocabulary::builtin("en").unwrap());
+        let rule = Rule::default();
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, l)| LineReport {
+                number: i + 1,
+                part1: parse_line(l, Digits::Ascii, &rule),
+                part2: parse_line_2(l, &scanner, Digits::Ascii, &rule),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn part_two_example_through_both_parts() {
+        let lines = [
+            "two1nine",
+            "eightwothree",
+            "abcone2threexyz",
+            "xtwone3four",
+            "4nineeightseven2",
+            "zoneight234",
+            "7pqrstsixteen",
+        ]
+        .map(|l| l.to_string());
+        let scanner = Scanner::new(&Vocabulary::builtin("en").unwrap());
+        let rule = Rule::default();
+        let part1 = sum_calibrations(&lines, |l| parse_line(l, Digits::Ascii, &rule));
+        let part2 = sum_calibrations(&lines, |l| parse_line_2(l, &scanner, Digits::Ascii, &rule));
+        assert_eq!(part1, Err("line 2: no digits".to_string()));
+        assert_eq!(part2, Ok(281));
+        // What `aoc test` compares with the fixture's `-` and `281`
+        assert_eq!([answer(&part1), answer(&part2)], ["-", "281"]);
+    }
+
+    #[test]
+    fn lines_without_digits() {
+        let lines = EXAMPLE.map(|l| l.to_string());
+        let rule = Rule::default();
+        assert_eq!(
+            sum_calibrations(&lines, |l| parse_line(l, Digits::Ascii, &rule)),
+            Err("line 2: no digits\nline 3: no digits".to_string())
+        );
+        assert_eq!(
+            sum_calibrations(&lines[..1], |l| parse_line(l, Digits::Ascii, &rule)),
+            Ok(11)
+        );
+    }
+
+    #[test]
+    fn lines_that_overflow() {
+        let lines = ["a1b2", "123456789012345678901234", "9x"].map(|l| l.to_string());
+        let rule = Rule {
+            pick: Pick::Numbers,
+            ..Rule::default()
+        };
+        assert_eq!(
+            sum_calibrations(&lines, |l| parse_line(l, Digits::Ascii, &rule)),
+            Err("line 2: calibration value overflows u64".to_string())
+        );
+        // Each line is 9 * 10^18 added to itself, which fits, but not twice over
+        let lines = ["9000000000000000000", "9000000000000000000"].map(|l| l.to_string());
+        let rule = Rule {
+            pick: Pick::Numbers,
+            combine: Combine::Sum,
+            ..Rule::default()
+        };
+        assert_eq!(
+            sum_calibrations(&lines, |l| parse_line(l, Digits::Ascii, &rule)),
+            Err("The sum of the calibration values overflows u64".to_string())
+        );
+    }
+
+    #[test]
+    fn diagnose() {
+        assert_eq!(
+            diagnostics::render(&reports(&EXAMPLE)),
+            "line 1: part 1 11 from 1 (digit at 3..4) and 1 (digit at 3..4); \
+             part 2 29 from 2 (word at 0..3) and 9 (word at 4..8); part 2 +18 (first 1 -> 2, last 1 -> 9)\n\
+             line 2: part 1 error: no digits; part 2 error: no digits\n\
+             line 3: part 1 error: no digits; part 2 83 from 8 (word at 0..5) and 3 (word at 7..12)\n\
+             line 4: part 1 77 from 7 (digit at 0..1) and 7 (digit at 0..1); \
+             part 2 76 from 7 (digit at 0..1) and 6 (word at 6..9); part 2 -1 (last 7 -> 6)\n\
+             4 lines\n\
+             part 1 errors on lines: 2, 3\n\
+             part 2 errors on lines: 2\n\
+             parts differ on lines: 1, 4\n"
+        );
+    }
+
+    #[test]
+    fn diagnose_large_differences() {
+        let calibration = |value| {
+            Ok(Calibration {
+                first: Vec::new(),
+                last: Vec::new(),
+                value,
+            })
+        };
+        let reports = [
+            LineReport {
+                number: 1,
+                part1: calibration(u64::MAX),
+                part2: calibration(1),
+            },
+            LineReport {
+                number: 2,
+                part1: calibration(0),
+                part2: calibration(u64::MAX),
+            },
+        ];
+        let rendered = diagnostics::render(&reports);
+        assert!(
+            rendered.contains("; part 2 -18446744073709551614 ("),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains("; part 2 +18446744073709551615 ("),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn diagnose_nothing_wrong() {
+        assert_eq!(
+            diagnostics::render(&reports(&["1abc2"])),
+            "line 1: part 1 12 from 1 (digit at 0..1) and 2 (digit at 4..5); \
+             part 2 12 from 1 (digit at 0..1) and 2 (digit at 4..5)\n\
+             1 lines\n\
+             part 1 errors on lines: none\n\
+             part 2 errors on lines: none\n\
+             parts differ on lines: none\n"
+        );
+    }
 }
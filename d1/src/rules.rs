@@ -0,0 +1,338 @@
+use crate::scanner::{Match, Origin};
+use std::cmp::Reverse;
+use std::fmt;
+
+/// Which tokens at both ends of a line make the calibration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    /// The first and last `n` digits, spelled or not
+    Digits(usize),
+    /// The first and last whole numbers, i.e. runs of adjacent digit characters.
+    /// A spelled out digit is a number on its own.
+    Numbers,
+}
+
+/// How the number at the start of a line and the one at its end are put together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    /// Written one after the other, as in the puzzle
+    Concat,
+    Sum,
+}
+
+/// What to do with words sharing letters, like "two" and "one" in "twone"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Keep them all, as in the puzzle
+    All,
+    /// Read from the start of the line, skipping the words that overlap one already read
+    LeftToRight,
+    /// Read from the end of the line, skipping the words that overlap one already read
+    RightToLeft,
+}
+
+/// Calibration rule, the puzzle's by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub pick: Pick,
+    pub combine: Combine,
+    pub overlap: Overlap,
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule {
+            pick: Pick::Digits(1),
+            combine: Combine::Concat,
+            overlap: Overlap::All,
+        }
+    }
+}
+
+/// Tokens at both ends of a line and the calibration value they make
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calibration {
+    pub first: Vec<Match>,
+    pub last: Vec<Match>,
+    pub value: u64,
+}
+
+/// Why a line has no calibration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invalid {
+    NoDigits,
+    /// The value doesn't fit in a `u64`, as with long runs of digits under `--pick numbers`
+    Overflow,
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Invalid::NoDigits => write!(f, "no digits"),
+            Invalid::Overflow => write!(f, "calibration value overflows u64"),
+        }
+    }
+}
+
+fn number(tokens: &[Match]) -> Option<u64> {
+    tokens
+        .iter()
+        .try_fold(0u64, |n, t| n.checked_mul(10)?.checked_add(t.value as u64))
+}
+
+impl Rule {
+    /// Read the rule from `--pick <digits:N|numbers>`, `--combine <concat|sum>` and
+    /// `--overlap <all|ltr|rtl>`
+    pub fn from_args() -> Rule {
+        let default = Rule::default();
+        let pick = match common::flag_value("--pick").as_deref() {
+            None => default.pick,
+            Some("numbers") => Pick::Numbers,
+            Some(pick) => match pick.strip_prefix("digits:").map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => Pick::Digits(n),
+                _ => panic!("Unknown pick: {}", pick),
+            },
+        };
+        let combine = match common::flag_value("--combine").as_deref() {
+            None | Some("concat") => Combine::Concat,
+            Some("sum") => Combine::Sum,
+            Some(combine) => panic!("Unknown combine: {}", combine),
+        };
+        let overlap = match common::flag_value("--overlap").as_deref() {
+            None | Some("all") => Overlap::All,
+            Some("ltr") => Overlap::LeftToRight,
+            Some("rtl") => Overlap::RightToLeft,
+            Some(overlap) => panic!("Unknown overlap: {}", overlap),
+        };
+        Rule {
+            pick,
+            combine,
+            overlap,
+        }
+    }
+
+    /// Drop the tokens the reading direction skips over, from tokens ordered by position
+    pub fn resolve_overlaps(&self, mut tokens: Vec<Match>) -> Vec<Match> {
+        match self.overlap {
+            Overlap::All => tokens,
+            Overlap::LeftToRight => {
+                // Longest first among the tokens starting at the same place
+                tokens.sort_by_key(|t| (t.start, Reverse(t.end)));
+                let mut result: Vec<Match> = Vec::new();
+                for token in tokens {
+                    if result.last().is_none_or(|l| token.start >= l.end) {
+                        result.push(token);
+                    }
+                }
+                result
+            }
+            Overlap::RightToLeft => {
+                tokens.sort_by_key(|t| (Reverse(t.end), t.start));
+                let mut result: Vec<Match> = Vec::new();
+                for token in tokens {
+                    if result.last().is_none_or(|l| token.end <= l.start) {
+                        result.push(token);
+                    }
+                }
+                result.reverse();
+                result
+            }
+        }
+    }
+
+    /// The calibration value of a line's tokens
+    pub fn apply(&self, tokens: &[Match]) -> Result<Calibration, Invalid> {
+        if tokens.is_empty() {
+            return Err(Invalid::NoDigits);
+        }
+        let (first, last) = match self.pick {
+            Pick::Digits(n) => {
+                let n = n.min(tokens.len());
+                (tokens[..n].to_vec(), tokens[tokens.len() - n..].to_vec())
+            }
+            Pick::Numbers => {
+                let mut numbers: Vec<Vec<Match>> = Vec::new();
+                for &token in tokens {
+                    match numbers.last_mut() {
+                        Some(number)
+                            if token.origin == Origin::Digit
+                                && number.last().is_some_and(|t| {
+                                    t.origin == Origin::Digit && t.end == token.start
+                                }) =>
+                        {
+                            number.push(token)
+                        }
+                        _ => numbers.push(vec![token]),
+                    }
+                }
+                (numbers[0].clone(), numbers[numbers.len() - 1].clone())
+            }
+        };
+        let (a, b) = (number(&first), number(&last));
+        let value = match (a, b, self.combine) {
+            (Some(a), Some(b), Combine::Concat) => {
+                (a.to_string() + &b.to_string()).parse::<u64>().ok()
+            }
+            (Some(a), Some(b), Combine::Sum) => a.checked_add(b),
+            _ => None,
+        }
+        .ok_or(Invalid::Overflow)?;
+        Ok(Calibration { first, last, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digit(start: usize, value: u32) -> Match {
+        Match {
+            start,
+            end: start + 1,
+            value,
+            origin: Origin::Digit,
+        }
+    }
+
+    fn word(start: usize, end: usize, value: u32) -> Match {
+        Match {
+            start,
+            end,
+            value,
+            origin: Origin::Word,
+        }
+    }
+
+    fn rule(pick: Pick, combine: Combine, overlap: Overlap) -> Rule {
+        Rule {
+            pick,
+            combine,
+            overlap,
+        }
+    }
+
+    /// The value of "12three45six" under a rule
+    fn value(pick: Pick, combine: Combine) -> Result<u64, Invalid> {
+        let tokens = [
+            digit(0, 1),
+            digit(1, 2),
+            word(2, 7, 3),
+            digit(7, 4),
+            digit(8, 5),
+            word(9, 12, 6),
+        ];
+        rule(pick, combine, Overlap::All)
+            .apply(&tokens)
+            .map(|c| c.value)
+    }
+
+    #[test]
+    fn pick() {
+        assert_eq!(value(Pick::Digits(1), Combine::Concat), Ok(16));
+        assert_eq!(value(Pick::Digits(2), Combine::Concat), Ok(1256));
+        // More digits than the line has takes all of them at both ends
+        assert_eq!(value(Pick::Digits(9), Combine::Concat), Ok(123456123456));
+        // 12, 3, 45 and 6, where the spelled out digits are numbers of their own
+        assert_eq!(value(Pick::Numbers, Combine::Concat), Ok(126));
+        let tokens = [
+            digit(0, 1),
+            digit(1, 2),
+            word(5, 8, 3),
+            digit(8, 4),
+            digit(9, 5),
+        ];
+        let calibration = rule(Pick::Numbers, Combine::Concat, Overlap::All)
+            .apply(&tokens)
+            .unwrap();
+        assert_eq!(calibration.first, tokens[..2]);
+        assert_eq!(calibration.last, tokens[3..]);
+        assert_eq!(calibration.value, 1245);
+        // Digits apart don't make a number
+        let tokens = [digit(0, 1), digit(2, 2)];
+        let calibration = rule(Pick::Numbers, Combine::Concat, Overlap::All)
+            .apply(&tokens)
+            .unwrap();
+        assert_eq!(calibration.value, 12);
+    }
+
+    #[test]
+    fn combine() {
+        assert_eq!(value(Pick::Digits(1), Combine::Sum), Ok(7));
+        assert_eq!(value(Pick::Digits(2), Combine::Sum), Ok(68));
+        assert_eq!(value(Pick::Numbers, Combine::Sum), Ok(18));
+        // A leading zero is lost when written one after the other
+        let tokens = [digit(0, 0), digit(1, 7)];
+        let value = |combine| {
+            rule(Pick::Digits(1), combine, Overlap::All)
+                .apply(&tokens)
+                .map(|c| c.value)
+        };
+        assert_eq!(value(Combine::Concat), Ok(7));
+        assert_eq!(value(Combine::Sum), Ok(7));
+    }
+
+    #[test]
+    fn no_digits_and_overflow() {
+        assert_eq!(Rule::default().apply(&[]), Err(Invalid::NoDigits));
+        let run = |n: usize| (0..n).map(|i| digit(i, 9)).collect::<Vec<_>>();
+        let numbers = |combine| rule(Pick::Numbers, combine, Overlap::All);
+        // u64::MAX has 20 digits, so 20 nines overflow on their own and 19 once added up
+        assert_eq!(
+            numbers(Combine::Sum).apply(&run(18)).map(|c| c.value),
+            Ok(1_999_999_999_999_999_998)
+        );
+        assert_eq!(
+            numbers(Combine::Sum).apply(&run(19)),
+            Err(Invalid::Overflow)
+        );
+        assert_eq!(
+            numbers(Combine::Sum).apply(&run(20)),
+            Err(Invalid::Overflow)
+        );
+        assert_eq!(
+            numbers(Combine::Concat).apply(&run(10)),
+            Err(Invalid::Overflow)
+        );
+        assert_eq!(
+            numbers(Combine::Concat).apply(&run(9)).map(|c| c.value),
+            Ok(999_999_999_999_999_999)
+        );
+        assert_eq!(
+            rule(Pick::Digits(30), Combine::Sum, Overlap::All).apply(&run(30)),
+            Err(Invalid::Overflow)
+        );
+    }
+
+    #[test]
+    fn overlap() {
+        // "eightwone": eight at 0..5, two at 4..7 and one at 6..9
+        let tokens = vec![word(0, 5, 8), word(4, 7, 2), word(6, 9, 1)];
+        let values = |overlap| {
+            rule(Pick::Digits(1), Combine::Concat, overlap)
+                .resolve_overlaps(tokens.clone())
+                .iter()
+                .map(|t| t.value)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(Overlap::All), [8, 2, 1]);
+        assert_eq!(values(Overlap::LeftToRight), [8, 1]);
+        assert_eq!(values(Overlap::RightToLeft), [8, 1]);
+        // "twone": two at 0..3 and one at 2..5
+        let tokens = vec![word(0, 3, 2), word(2, 5, 1)];
+        let ends = |overlap| {
+            let tokens =
+                rule(Pick::Digits(1), Combine::Concat, overlap).resolve_overlaps(tokens.clone());
+            rule(Pick::Digits(1), Combine::Concat, overlap)
+                .apply(&tokens)
+                .map(|c| c.value)
+        };
+        assert_eq!(ends(Overlap::All), Ok(21));
+        assert_eq!(ends(Overlap::LeftToRight), Ok(22));
+        assert_eq!(ends(Overlap::RightToLeft), Ok(11));
+        // The longer word wins among those starting at the same place
+        let tokens = vec![digit(0, 3), word(1, 4, 1), word(1, 6, 7)];
+        let kept =
+            rule(Pick::Digits(1), Combine::Concat, Overlap::LeftToRight).resolve_overlaps(tokens);
+        assert_eq!(kept, [digit(0, 3), word(1, 6, 7)]);
+    }
+}
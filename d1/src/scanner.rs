@@ -0,0 +1,326 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Number words and the digit each of them spells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+const ENGLISH: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const GERMAN: [&str; 10] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const FRENCH: [&str; 10] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+const SPANISH: [&str; 10] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+
+/// Names of the built-in vocabularies
+pub const BUILTIN: [&str; 5] = ["en", "zero", "de", "fr", "es"];
+
+impl Vocabulary {
+    pub fn new() -> Vocabulary {
+        Vocabulary::default()
+    }
+
+    /// Add a word, matched case-insensitively
+    pub fn add(mut self, word: &str, value: u32) -> Vocabulary {
+        assert!(value < 10, "{} must spell a single digit", word);
+        assert!(!word.is_empty());
+        self.words.push((word.to_lowercase(), value));
+        self
+    }
+
+    pub fn extend(mut self, other: Vocabulary) -> Vocabulary {
+        self.words.extend(other.words);
+        self
+    }
+
+    /// The built-in word lists: `en` (one to nine, as in the puzzle), `zero`, `de`, `fr` and `es`
+    pub fn builtin(name: &str) -> Option<Vocabulary> {
+        let (words, skip) = match name {
+            "en" => (&ENGLISH[..], 1),
+            "zero" => (&ENGLISH[..1], 0),
+            "de" => (&GERMAN[..], 0),
+            "fr" => (&FRENCH[..], 0),
+            "es" => (&SPANISH[..], 0),
+            _ => return None,
+        };
+        Some(
+            words
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .fold(Vocabulary::new(), |v, (i, w)| v.add(w, i as u32)),
+        )
+    }
+
+    /// One `word digit` pair per line. A word stands for a single digit of the calibration
+    /// value, so values above 9 are rejected.
+    pub fn parse(contents: &str) -> Result<Vocabulary, String> {
+        let mut vocabulary = Vocabulary::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (word, value) = line
+                .trim()
+                .rsplit_once(' ')
+                .map(|(w, v)| (w.trim(), v.parse::<u32>()))
+                .filter(|(w, _)| !w.is_empty())
+                .ok_or_else(|| format!("line {}: expected <word> <digit>", i + 1))?;
+            match value {
+                Ok(value) if value < 10 => vocabulary = vocabulary.add(word, value),
+                _ => {
+                    return Err(format!(
+                        "line {}: {} must spell a single digit",
+                        i + 1,
+                        word
+                    ))
+                }
+            }
+        }
+        Ok(vocabulary)
+    }
+
+    /// A comma separated list of built-in names and files in the format of `parse`
+    pub fn from_spec(spec: &str) -> Result<Vocabulary, String> {
+        spec.split(',').try_fold(Vocabulary::new(), |v, name| {
+            let other = match Vocabulary::builtin(name) {
+                Some(builtin) => builtin,
+                None => {
+                    let contents = std::fs::read_to_string(name).map_err(|e| {
+                        format!(
+                            "{} is neither a built-in vocabulary ({}) nor a file: {}",
+                            name,
+                            BUILTIN.join(", "),
+                            e
+                        )
+                    })?;
+                    Vocabulary::parse(&contents).map_err(|e| format!("{}, {}", name, e))?
+                }
+            };
+            Ok(v.extend(other))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Digit,
+    Word,
+}
+
+/// A token found in a line, with its byte range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub value: u32,
+    pub origin: Origin,
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let origin = match self.origin {
+            Origin::Digit => "digit",
+            Origin::Word => "word",
+        };
+        write!(
+            f,
+            "{} ({} at {}..{})",
+            self.value, origin, self.start, self.end
+        )
+    }
+}
+
+/// Aho–Corasick automaton finding every word of a vocabulary in one pass, overlaps included
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    next: Vec<HashMap<char, usize>>,
+    fail: Vec<usize>,
+    /// Words ending in each state as `(length in chars, digit)`
+    output: Vec<Vec<(usize, u32)>>,
+}
+
+impl Scanner {
+    pub fn new(vocabulary: &Vocabulary) -> Scanner {
+        let mut scanner = Scanner {
+            next: vec![HashMap::new()],
+            fail: vec![0],
+            output: vec![vec![]],
+        };
+        for (word, value) in vocabulary.words.iter() {
+            let mut state = 0;
+            for ch in word.chars() {
+                state = match scanner.next[state].get(&ch) {
+                    Some(&s) => s,
+                    None => {
+                        scanner.next.push(HashMap::new());
+                        scanner.fail.push(0);
+                        scanner.output.push(vec![]);
+                        let s = scanner.next.len() - 1;
+                        scanner.next[state].insert(ch, s);
+                        s
+                    }
+                };
+            }
+            scanner.output[state].push((word.chars().count(), *value));
+        }
+        // Breadth first, so the fallback of a state is always done before the state itself
+        let mut queue = scanner.next[0].values().copied().collect::<VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            let edges = scanner.next[state]
+                .iter()
+                .map(|(&ch, &s)| (ch, s))
+                .collect::<Vec<_>>();
+            for (ch, s) in edges {
+                let mut fallback = scanner.fail[state];
+                while fallback != 0 && !scanner.next[fallback].contains_key(&ch) {
+                    fallback = scanner.fail[fallback];
+                }
+                scanner.fail[s] = scanner.next[fallback].get(&ch).copied().unwrap_or(0);
+                let inherited = scanner.output[scanner.fail[s]].clone();
+                scanner.output[s].extend(inherited);
+                queue.push_back(s);
+            }
+        }
+        scanner
+    }
+
+    /// All the words in `text`, including overlapping ones like "two" and "one" in "twone",
+    /// ordered by position
+    pub fn find_overlapping(&self, text: &str) -> Vec<Match> {
+        let mut matches = Vec::new();
+        // Byte offset of every lower-cased char, as lower-casing can turn one char into several
+        let mut offsets = Vec::new();
+        let mut state = 0;
+        for (i, ch) in text.char_indices() {
+            for lower in ch.to_lowercase() {
+                offsets.push(i);
+                while state != 0 && !self.next[state].contains_key(&lower) {
+                    state = self.fail[state];
+                }
+                state = self.next[state].get(&lower).copied().unwrap_or(0);
+                for &(len, value) in self.output[state].iter() {
+                    matches.push(Match {
+                        start: offsets[offsets.len() - len],
+                        end: i + ch.len_utf8(),
+                        value,
+                        origin: Origin::Word,
+                    });
+                }
+            }
+        }
+        matches.sort_by_key(|m| (m.start, m.end));
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(vocabulary: &Vocabulary, text: &str) -> Vec<(usize, usize, u32)> {
+        Scanner::new(vocabulary)
+            .find_overlapping(text)
+            .iter()
+            .map(|m| (m.start, m.end, m.value))
+            .collect()
+    }
+
+    fn english() -> Vocabulary {
+        Vocabulary::builtin("en").unwrap()
+    }
+
+    #[test]
+    fn overlapping_words() {
+        assert_eq!(words(&english(), "twone"), [(0, 3, 2), (2, 5, 1)]);
+        assert_eq!(words(&english(), "eightwo"), [(0, 5, 8), (4, 7, 2)]);
+        assert_eq!(
+            words(&english(), "oneightwone"),
+            [(0, 3, 1), (2, 7, 8), (6, 9, 2), (8, 11, 1)]
+        );
+        assert_eq!(words(&english(), "7pqrstsixteen"), [(6, 9, 6)]);
+    }
+
+    #[test]
+    fn builtin_vocabularies() {
+        for (name, text, expected) in [
+            (
+                "en",
+                "zeroonetwothreefourfivesixseveneightnine",
+                vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+            ),
+            ("zero", "zeroone", vec![0]),
+            (
+                "de",
+                "nulleinszweidreivierfünfsechssiebenachtneun",
+                (0..10).collect(),
+            ),
+            (
+                "fr",
+                "zéroundeuxtroisquatrecinqsixsepthuitneuf",
+                (0..10).collect(),
+            ),
+            (
+                "es",
+                "cerounodostrescuatrocincoseissieteochonueve",
+                (0..10).collect(),
+            ),
+        ] {
+            let vocabulary = Vocabulary::builtin(name).unwrap();
+            let values = words(&vocabulary, text)
+                .into_iter()
+                .map(|(_, _, v)| v)
+                .collect::<Vec<_>>();
+            assert_eq!(values, expected, "{}", name);
+        }
+        assert_eq!(Vocabulary::builtin("eng"), None);
+        for name in BUILTIN {
+            assert!(Vocabulary::builtin(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn case_folding() {
+        assert_eq!(words(&english(), "TwOne"), [(0, 3, 2), (2, 5, 1)]);
+        let german = Vocabulary::builtin("de").unwrap();
+        assert_eq!(words(&german, "FÜNF"), [(0, 5, 5)]);
+        let french = Vocabulary::builtin("fr").unwrap();
+        assert_eq!(words(&french, "ZÉRO"), [(0, 5, 0)]);
+        // Lower-casing "İ" gives two chars, the match still covers the bytes of the original
+        let custom = Vocabulary::new().add("i̇ki", 2);
+        assert_eq!(words(&custom, "xİKİ"), [(1, 6, 2)]);
+    }
+
+    #[test]
+    fn parse_vocabulary() {
+        let vocabulary = Vocabulary::parse("\ntwelve 2\n  dozen 1 \n").unwrap();
+        assert_eq!(words(&vocabulary, "dozentwelve"), [(0, 5, 1), (5, 11, 2)]);
+        assert_eq!(
+            Vocabulary::parse("one 1\ntwo\n"),
+            Err("line 2: expected <word> <digit>".to_string())
+        );
+        assert_eq!(
+            Vocabulary::parse("one 1\nten 10\n"),
+            Err("line 2: ten must spell a single digit".to_string())
+        );
+        assert!(Vocabulary::parse("one x").is_err());
+    }
+
+    #[test]
+    fn spec_errors() {
+        assert_eq!(
+            Vocabulary::from_spec("en,de").unwrap(),
+            english().extend(Vocabulary::builtin("de").unwrap())
+        );
+        let error = Vocabulary::from_spec("en,eng").unwrap_err();
+        assert!(error.starts_with("eng is neither a built-in vocabulary (en, zero, de, fr, es)"));
+    }
+}
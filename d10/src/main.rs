@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -27,7 +28,7 @@ impl Direction {
             _ => panic!("Invalid direction: {:?}", self),
         }
     }
-    fn to_pos(&self) -> (i32, i32) {
+    fn to_pos(self) -> (i32, i32) {
         match self {
             Direction::Up => (-1, 0),
             Direction::Down => (1, 0),
@@ -52,7 +53,7 @@ impl Pipe {
     }
 }
 
-fn parse_lines(lines: &Vec<String>) -> ((usize, usize), Vec<Vec<Pipe>>) {
+fn parse_lines(lines: &[String]) -> ((usize, usize), Vec<Vec<Pipe>>) {
     let mut row = 0;
     let mut col = 0;
     let map = lines
@@ -98,42 +99,45 @@ fn parse_lines(lines: &Vec<String>) -> ((usize, usize), Vec<Vec<Pipe>>) {
     ((row, col), map)
 }
 
-fn get_loop_length_and_area(map: &Vec<Vec<Pipe>>, start: (usize, usize)) -> (usize, u64) {
+fn get_loop_length_and_area(map: &[Vec<Pipe>], start: (usize, usize)) -> (usize, u64) {
     let (row, col) = start;
     let (mut r, mut c) = (row, col);
     let mut length = 0;
     let mut direction = Direction::Empty;
     // Determine initial direction
-    for d in vec![
+    for d in [
         Direction::Up,
         Direction::Down,
         Direction::Left,
         Direction::Right,
     ] {
         let (dr, dc) = d.to_pos();
-        if let Some(_) = map[(r as i32 + dr) as usize][(c as i32 + dc) as usize].extend_from(d) {
+        let (nr, nc) = (r as i32 + dr, c as i32 + dc);
+        let inside =
+            nr >= 0 && nc >= 0 && (nr as usize) < map.len() && (nc as usize) < map[0].len();
+        if inside && map[nr as usize][nc as usize].extend_from(d).is_some() {
             direction = d;
             break;
         }
     }
+    let first = direction;
     let mut coords = vec![(r, c)];
-    let mut counter = vec![0; 4];
+    let mut counter = [0; 4];
 
     // Get loop length and determine clockwise or counter-clockwise
-    let mut visited = vec![vec![false; map[0].len()]; map.len()];
     let mut angle = 0;
-    visited[r][c] = true;
     loop {
         let (dr, dc) = direction.to_pos();
         r = (r as i32 + dr) as usize;
         c = (c as i32 + dc) as usize;
         coords.push((r, c));
         length += 1;
-        if visited[r][c] {
-            break;
+        if (r, c) == start {
+            // Back at the start, which turns into the first direction
+            direction = first;
+        } else {
+            direction = map[r][c].extend_from(direction).unwrap();
         }
-        visited[r][c] = true;
-        direction = map[r][c].extend_from(direction).unwrap();
         let (ddr, ddc) = direction.to_pos();
         match dr * ddc - dc * ddr {
             1 => {
@@ -148,6 +152,9 @@ fn get_loop_length_and_area(map: &Vec<Vec<Pipe>>, start: (usize, usize)) -> (usi
                 counter[2] += 1;
             }
         };
+        if (r, c) == start {
+            break;
+        }
     }
     // Swap corner counters if angle is -360
     match angle {
@@ -155,25 +162,155 @@ fn get_loop_length_and_area(map: &Vec<Vec<Pipe>>, start: (usize, usize)) -> (usi
         -360 => (counter[1], counter[3]) = (counter[3], counter[1]),
         _ => panic!("Unexpected angle: {}", angle),
     }
-    // Calculate area using Shoelace formula
+    // Calculate area using Shoelace formula, negative when going round counter-clockwise
     let showlace = coords.windows(2).fold(0, |acc, xy| {
         let (x, y) = (xy[0], xy[1]);
         let (rx, cx) = x;
         let (ry, cy) = y;
         acc + (rx * cy) as i32 - (cx * ry) as i32
     });
-    // Calculate areas from corners and edges. This is the only source of error
-    let extra = ((1..4).map(|i| i * counter[i]).sum::<usize>() + 3) / 4;
+    // Calculate areas from corners and edges, a quarter of a tile per inside corner, half
+    // per straight and three quarters per outside corner
+    let extra = (1..4).map(|i| i * counter[i]).sum::<usize>() / 4;
     // The desired area is the total area minus the area where the loop is
-    let area = showlace as u64 / 2 - extra as u64;
+    let area = showlace.unsigned_abs() as u64 / 2 - extra as u64;
+    common::visualise("loop", || {
+        let points = coords
+            .iter()
+            .map(|(r, c)| format!("{},{}", c, r))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -1 {} {}"><polygon points="{}" fill="#fd8" stroke="#c60" stroke-width="0.3"/></svg>"##,
+            map[0].len() + 1,
+            map.len() + 1,
+            points
+        )
+    });
 
     (length, area)
 }
 
+/// Same as `get_loop_length_and_area`, but flood fills the outside of the loop on a map
+/// with every tile doubled, so the gaps between adjacent pipes become tiles too
+fn get_loop_length_and_area_flood_fill(map: &[Vec<Pipe>], start: (usize, usize)) -> (usize, u64) {
+    let (mut r, mut c) = start;
+    let mut direction = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ]
+    .into_iter()
+    .find(|d| {
+        let (dr, dc) = d.to_pos();
+        let (nr, nc) = ((r as i32 + dr) as usize, (c as i32 + dc) as usize);
+        map.get(nr)
+            .and_then(|row| row.get(nc))
+            .is_some_and(|pipe| pipe.extend_from(*d).is_some())
+    })
+    .unwrap();
+    // Tile (r, c) is at (2r + 1, 2c + 1), leaving a border for the flood to go around
+    let (rows, cols) = (2 * map.len() + 1, 2 * map[0].len() + 1);
+    let mut wall = vec![vec![false; cols]; rows];
+    let mut length = 0;
+    loop {
+        wall[2 * r + 1][2 * c + 1] = true;
+        let (dr, dc) = direction.to_pos();
+        wall[(2 * r as i32 + 1 + dr) as usize][(2 * c as i32 + 1 + dc) as usize] = true;
+        r = (r as i32 + dr) as usize;
+        c = (c as i32 + dc) as usize;
+        length += 1;
+        if (r, c) == start {
+            break;
+        }
+        direction = map[r][c].extend_from(direction).unwrap();
+    }
+    let mut outside = vec![vec![false; cols]; rows];
+    let mut stack = vec![(0, 0)];
+    outside[0][0] = true;
+    while let Some((r, c)) = stack.pop() {
+        for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (nr, nc) = (r as i32 + dr, c as i32 + dc);
+            if nr < 0 || nc < 0 || nr as usize >= rows || nc as usize >= cols {
+                continue;
+            }
+            let (nr, nc) = (nr as usize, nc as usize);
+            if !wall[nr][nc] && !outside[nr][nc] {
+                outside[nr][nc] = true;
+                stack.push((nr, nc));
+            }
+        }
+    }
+    let area = (0..map.len())
+        .flat_map(|r| (0..map[0].len()).map(move |c| (2 * r + 1, 2 * c + 1)))
+        .filter(|&(r, c)| !wall[r][c] && !outside[r][c])
+        .count();
+    (length, area as u64)
+}
+
 fn main() {
-    let lines = read_lines("input.txt");
-    let (start, map) = parse_lines(&lines);
-    let (length, area) = get_loop_length_and_area(&map, start);
-    println!("{}", length / 2);
-    println!("{}", area);
+    common::init_tracing();
+    let (start, map) = info_span!("parse").in_scope(|| parse_lines(&read_lines("input.txt")));
+    // Both parts come out of the same walk along the loop
+    info_span!("solve").in_scope(|| {
+        let (length, area) = common::strategy::choose(&[
+            ("shoelace", &|| get_loop_length_and_area(&map, start)),
+            ("flood-fill", &|| {
+                get_loop_length_and_area_flood_fill(&map, start)
+            }),
+        ]);
+        println!("{}", length / 2);
+        println!("{}", area);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn both(lines: &[&str]) -> [(usize, u64); 2] {
+        let (start, map) = parse_lines(&lines.iter().map(|l| l.to_string()).collect::<Vec<_>>());
+        [
+            get_loop_length_and_area(&map, start),
+            get_loop_length_and_area_flood_fill(&map, start),
+        ]
+    }
+
+    #[test]
+    fn farthest() {
+        let lines = ["..F7.", ".FJ|.", "SJ.L7", "|F--J", "LJ..."];
+        for (length, _) in both(&lines) {
+            assert_eq!(length / 2, 8);
+        }
+    }
+
+    #[test]
+    fn enclosed() {
+        let squeezed = [
+            "...........",
+            ".S-------7.",
+            ".|F-----7|.",
+            ".||.....||.",
+            ".||.....||.",
+            ".|L-7.F-J|.",
+            ".|..|.|..|.",
+            ".L--J.L--J.",
+            "...........",
+        ];
+        assert_eq!(both(&squeezed).map(|(_, area)| area), [4, 4]);
+        let larger = [
+            ".F----7F7F7F7F-7....",
+            ".|F--7||||||||FJ....",
+            ".||.FJ||||||||L7....",
+            "FJL7L7LJLJ||LJ.L-7..",
+            "L--J.L7...LJS7F-7L7.",
+            "....F-J..F7FJ|L7L7L7",
+            "....L7.F7||L7|.L7L7|",
+            ".....|FJLJ|FJ|F7|.LJ",
+            "....FJL-7.||.||||...",
+            "....L---J.LJ.LJLJ...",
+        ];
+        assert_eq!(both(&larger).map(|(_, area)| area), [8, 8]);
+    }
 }
@@ -1,5 +1,7 @@
+use grid_cell::GridCell;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,9 +10,11 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
 enum Space {
+    #[cell('.')]
     Empty,
+    #[cell('#')]
     Galaxy,
 }
 
@@ -22,13 +26,12 @@ fn parse_input(input: Vec<String>, span_size: usize) -> Vec<(usize, usize)> {
         .map(|(r, s)| {
             s.chars()
                 .enumerate()
-                .map(|(c, ch)| match ch {
-                    '.' => Space::Empty,
-                    '#' => {
+                .map(|(c, ch)| {
+                    let space = Space::try_from(ch).unwrap();
+                    if space == Space::Galaxy {
                         galaxies.push((r, c));
-                        Space::Galaxy
                     }
-                    _ => panic!("Invalid input"),
+                    space
                 })
                 .collect::<Vec<_>>()
         })
@@ -58,24 +61,28 @@ fn parse_input(input: Vec<String>, span_size: usize) -> Vec<(usize, usize)> {
 
 fn calculate_distance(coords: Vec<(usize, usize)>) -> Vec<usize> {
     (0..coords.len())
-        .map(|i| {
+        .flat_map(|i| {
             (i + 1..coords.len())
                 .map(|j| {
-                    (coords[i].0 as isize - coords[j].0 as isize).abs() as usize
-                        + (coords[i].1 as isize - coords[j].1 as isize).abs() as usize
+                    (coords[i].0 as isize - coords[j].0 as isize).unsigned_abs()
+                        + (coords[i].1 as isize - coords[j].1 as isize).unsigned_abs()
                 })
                 .collect::<Vec<_>>()
         })
-        .flatten()
         .collect::<Vec<_>>()
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let map = parse_input(lines.to_owned(), 2);
-    let dist = calculate_distance(map);
-    println!("{}", dist.iter().sum::<usize>());
-    let map = parse_input(lines, 1_000_000);
-    let dist = calculate_distance(map);
-    println!("{}", dist.iter().sum::<usize>());
+    common::init_tracing();
+    let lines = info_span!("parse").in_scope(|| read_lines("input.txt"));
+    info_span!("part1").in_scope(|| {
+        let map = parse_input(lines.to_owned(), 2);
+        let dist = calculate_distance(map);
+        println!("{}", dist.iter().sum::<usize>());
+    });
+    info_span!("part2").in_scope(|| {
+        let map = parse_input(lines, 1_000_000);
+        let dist = calculate_distance(map);
+        println!("{}", dist.iter().sum::<usize>());
+    });
 }
@@ -1,5 +1,7 @@
+use grid_cell::GridCell;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,10 +10,13 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
 enum Spring {
+    #[cell('?')]
     Unknown,
+    #[cell('.')]
     Operating,
+    #[cell('#')]
     Damaged,
 }
 
@@ -50,18 +55,10 @@ fn transition(state: State, spring: Spring) -> Vec<usize> {
     }
 }
 
-fn parse_line(line: &String) -> (Vec<Spring>, Vec<usize>) {
+fn parse_line(line: &str) -> (Vec<Spring>, Vec<usize>) {
     let parts = line.split(" ").collect::<Vec<_>>();
     assert!(parts.len() == 2);
-    let status = parts[0]
-        .chars()
-        .map(|c| match c {
-            '.' => Spring::Operating,
-            '#' => Spring::Damaged,
-            '?' => Spring::Unknown,
-            _ => panic!("Invalid status"),
-        })
-        .collect::<Vec<_>>();
+    let status = grid_cell::parse_row(parts[0]).unwrap();
     let numbers = parts[1]
         .split(",")
         .map(|s| s.parse::<usize>().unwrap())
@@ -69,19 +66,19 @@ fn parse_line(line: &String) -> (Vec<Spring>, Vec<usize>) {
     (status, numbers)
 }
 
-fn calculate_combinitions(springs: &Vec<Spring>, numbers: &Vec<usize>) -> usize {
+fn calculate_combinitions(springs: &[Spring], numbers: &[usize]) -> usize {
     let mut states = vec![];
     for n in numbers.iter() {
         states.push(State::Start);
         states.extend(vec![State::Match; *n - 1]);
         states.push(State::End);
     }
-    states = vec![
+    states = [
         states.split_last().unwrap().1.to_owned(),
         vec![State::Terminal],
     ]
     .concat();
-    let mut counter = vec![0 as usize; states.len()];
+    let mut counter = vec![0_usize; states.len()];
     counter[0] = 1;
     for &s in springs {
         let mut _counter = vec![0; states.len()];
@@ -97,37 +94,43 @@ fn calculate_combinitions(springs: &Vec<Spring>, numbers: &Vec<usize>) -> usize
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let data = lines.iter().map(parse_line).collect::<Vec<_>>();
-    let result = data
-        .iter()
-        .map(|(status, numbers)| calculate_combinitions(status, numbers))
-        // .collect::<Vec<_>>();
-        .sum::<usize>();
-    println!("{}", result);
-    let data = lines
-        .iter()
-        .map(parse_line)
-        .map(|(status, numbers)| {
-            (
-                status
-                    .repeat(5)
-                    .chunks(status.len())
-                    .fold(vec![], |acc, x| {
-                        vec![acc, x.to_owned(), vec![Spring::Unknown]].concat()
-                    })
-                    .split_last()
-                    .unwrap()
-                    .1
-                    .to_owned(),
-                numbers.repeat(5),
-            )
-        })
-        .collect::<Vec<_>>();
-    let result = data
-        .iter()
-        .map(|(status, numbers)| calculate_combinitions(status, numbers))
-        // .collect::<Vec<_>>();
-        .sum::<usize>();
-    println!("{}", result);
+    common::init_tracing();
+    let data = info_span!("parse").in_scope(|| {
+        let lines = read_lines("input.txt");
+        lines.iter().map(|l| parse_line(l)).collect::<Vec<_>>()
+    });
+    info_span!("part1").in_scope(|| {
+        let result = data
+            .iter()
+            .map(|(status, numbers)| calculate_combinitions(status, numbers))
+            // .collect::<Vec<_>>();
+            .sum::<usize>();
+        println!("{}", result);
+    });
+    info_span!("part2").in_scope(|| {
+        let data = data
+            .iter()
+            .map(|(status, numbers)| {
+                (
+                    status
+                        .repeat(5)
+                        .chunks(status.len())
+                        .fold(vec![], |acc, x| {
+                            [acc, x.to_owned(), vec![Spring::Unknown]].concat()
+                        })
+                        .split_last()
+                        .unwrap()
+                        .1
+                        .to_owned(),
+                    numbers.repeat(5),
+                )
+            })
+            .collect::<Vec<_>>();
+        let result = data
+            .iter()
+            .map(|(status, numbers)| calculate_combinitions(status, numbers))
+            // .collect::<Vec<_>>();
+            .sum::<usize>();
+        println!("{}", result);
+    });
 }
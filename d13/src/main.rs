@@ -1,5 +1,7 @@
+use grid_cell::GridCell;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,28 +10,18 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
 enum Terrain {
+    #[cell('.')]
     Ash,
+    #[cell('#')]
     Rock,
 }
 
 fn parse_lines(lines: Vec<String>) -> Vec<Vec<Vec<Terrain>>> {
     lines
         .split(|s| s.is_empty())
-        .map(|s| {
-            s.iter()
-                .map(|l| {
-                    l.chars()
-                        .map(|c| match c {
-                            '.' => Terrain::Ash,
-                            '#' => Terrain::Rock,
-                            _ => panic!("Unknown terrain type: {}", c),
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-        })
+        .map(|s| grid_cell::parse_grid(s).unwrap())
         .collect()
 }
 
@@ -80,9 +72,10 @@ fn locate_mirror(terrain: Vec<Vec<Terrain>>, smudge: usize) -> (Option<usize>, O
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let terrains = parse_lines(lines);
-    for smudge in [0, 1] {
+    common::init_tracing();
+    let terrains = info_span!("parse").in_scope(|| parse_lines(read_lines("input.txt")));
+    for (smudge, span) in [(0, info_span!("part1")), (1, info_span!("part2"))] {
+        let _guard = span.enter();
         let mirrors = terrains
             .iter()
             .map(|t| locate_mirror(t.to_owned(), smudge))
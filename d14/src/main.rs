@@ -1,5 +1,9 @@
+use common::Counter;
+use grid_cell::GridCell;
 use std::fs::File;
 use std::io::Read;
+use tracing::debug;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,10 +12,13 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
 enum Grid {
+    #[cell('O')]
     Rock,
+    #[cell('#')]
     Fixed,
+    #[cell('.')]
     Empty,
 }
 
@@ -24,19 +31,7 @@ enum Direction {
 }
 
 fn parse_lines(lines: Vec<String>) -> Vec<Vec<Grid>> {
-    lines
-        .iter()
-        .map(|line| {
-            line.chars()
-                .map(|c| match c {
-                    'O' => Grid::Rock,
-                    '#' => Grid::Fixed,
-                    '.' => Grid::Empty,
-                    _ => panic!("Invalid character"),
-                })
-                .collect()
-        })
-        .collect()
+    grid_cell::parse_grid(&lines).unwrap()
 }
 
 fn get_deltas(direction: Direction) -> (i32, i32) {
@@ -60,35 +55,32 @@ fn tilt(platform: Vec<Vec<Grid>>, direction: Direction) -> Vec<Vec<Grid>> {
             1 => Box::new((0..col).rev()) as Box<dyn Iterator<Item = usize>>,
             _ => Box::new(0..col) as Box<dyn Iterator<Item = usize>>,
         } {
-            match platform[i][j] {
-                Grid::Rock => {
-                    let (mut r, mut c) = (i, j);
-                    loop {
-                        let nr = r as i32 + dr;
-                        let nc = c as i32 + dc;
-                        if nr >= 0
-                            && nr < row as i32
-                            && nc >= 0
-                            && nc < col as i32
-                            && platform[nr as usize][nc as usize] == Grid::Empty
-                        {
-                            platform[nr as usize][nc as usize] = Grid::Rock;
-                            platform[r][c] = Grid::Empty;
-                            r = nr as usize;
-                            c = nc as usize;
-                        } else {
-                            break;
-                        }
+            if platform[i][j] == Grid::Rock {
+                let (mut r, mut c) = (i, j);
+                loop {
+                    let nr = r as i32 + dr;
+                    let nc = c as i32 + dc;
+                    if nr >= 0
+                        && nr < row as i32
+                        && nc >= 0
+                        && nc < col as i32
+                        && platform[nr as usize][nc as usize] == Grid::Empty
+                    {
+                        platform[nr as usize][nc as usize] = Grid::Rock;
+                        platform[r][c] = Grid::Empty;
+                        r = nr as usize;
+                        c = nc as usize;
+                    } else {
+                        break;
                     }
                 }
-                _ => {}
             }
         }
     }
     platform
 }
 
-fn calc_load(platform: &Vec<Vec<Grid>>) -> usize {
+fn calc_load(platform: &[Vec<Grid>]) -> usize {
     platform
         .iter()
         .enumerate()
@@ -96,7 +88,10 @@ fn calc_load(platform: &Vec<Vec<Grid>>) -> usize {
         .sum()
 }
 
-fn cycle(platform: &Vec<Vec<Grid>>) -> Vec<Vec<Grid>> {
+static CYCLES: Counter = Counter::new("cycles");
+
+fn cycle(platform: &[Vec<Grid>]) -> Vec<Vec<Grid>> {
+    CYCLES.incr();
     let mut platform = platform.to_owned();
     platform = tilt(platform, Direction::North);
     platform = tilt(platform, Direction::West);
@@ -106,11 +101,14 @@ fn cycle(platform: &Vec<Vec<Grid>>) -> Vec<Vec<Grid>> {
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let mut platform = parse_lines(lines);
-    let tilt_north_once = tilt(platform.clone(), Direction::North);
-    let load = calc_load(&tilt_north_once);
-    println!("{}", load);
+    common::init_tracing();
+    let mut platform = info_span!("parse").in_scope(|| parse_lines(read_lines("input.txt")));
+    info_span!("part1").in_scope(|| {
+        let tilt_north_once = tilt(platform.clone(), Direction::North);
+        let load = calc_load(&tilt_north_once);
+        println!("{}", load);
+    });
+    let _span = info_span!("part2").entered();
     // Burn in stage before determining the period
     // This number is arbitrary, but it should be large enough
     // to ensure that the platform has reached a stable state
@@ -134,6 +132,11 @@ fn main() {
     for _ in 0..((1_000_000_000 - burn_in) % period) {
         platform = cycle(&platform);
     }
+    debug!(
+        "Platform after 10^9 cycles:\n{}",
+        grid_cell::render_grid(&platform)
+    );
     let load = calc_load(&platform);
     println!("{}", load);
+    CYCLES.report();
 }
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,82 +10,156 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-fn parse_input(line: &String) -> Vec<String> {
+fn parse_input(line: &str) -> Vec<String> {
     line.split(",").map(|s| s.to_string()).collect()
 }
 
-fn hash(s: &String) -> u8 {
+fn hash(s: &str) -> u8 {
     s.chars()
         .fold(0, |acc, c| ((acc as u16 + c as u16) * 17) as u8)
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 struct Lens {
     pub label: String,
     pub focal: u8,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 enum Instruction {
-    Remove(usize, Lens),
-    Set(usize, Lens),
+    Remove(String),
+    Set(Lens),
 }
 
-fn parse_instruction(s: &String) -> Instruction {
+impl Instruction {
+    fn label(&self) -> &str {
+        match self {
+            Instruction::Remove(label) => label,
+            Instruction::Set(lens) => &lens.label,
+        }
+    }
+
+    /// The box the label hashes to
+    fn box_index(&self) -> usize {
+        hash(self.label()) as usize
+    }
+}
+
+/// A step as written, which part 1 hashes as it is, and what it does
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+struct Step {
+    text: String,
+    instruction: Instruction,
+}
+
+fn parse_instruction(s: &str) -> Instruction {
     if s.contains('=') {
         let parts = s.split("=").collect::<Vec<_>>();
         let label = parts[0].to_string();
         let focal = parts[1].parse::<u8>().unwrap();
-        Instruction::Set(hash(&label) as usize, Lens { label, focal })
+        Instruction::Set(Lens { label, focal })
     } else {
-        let label = s.replace("-", "");
-        Instruction::Remove(hash(&label) as usize, Lens { label, focal: 0 })
+        Instruction::Remove(s.replace("-", ""))
     }
 }
 
+fn parse_step(s: &str) -> Step {
+    Step {
+        text: s.to_string(),
+        instruction: parse_instruction(s),
+    }
+}
+
+fn hash_sum(steps: &[Step]) -> u32 {
+    steps.iter().map(|s| hash(&s.text) as u32).sum()
+}
+
 fn apply_instructions(instructions: Vec<Instruction>) -> Vec<Vec<Lens>> {
     let mut boxes = vec![vec![]; 256] as Vec<Vec<Lens>>;
-    instructions
-        .iter()
-        .for_each(|instruction| match instruction {
-            Instruction::Set(hash, lens) => {
-                let index = boxes[*hash].iter().position(|l| l.label == lens.label);
-                match index {
-                    Some(index) => boxes[*hash][index] = lens.clone(),
-                    None => boxes[*hash].push(lens.clone()),
-                }
-            }
-            Instruction::Remove(hash, lens) => {
-                let index = boxes[*hash].iter().position(|l| l.label == lens.label);
-                match index {
-                    Some(index) => drop(boxes[*hash].remove(index)),
-                    _ => {}
+    instructions.iter().for_each(|instruction| {
+        let lenses = &mut boxes[instruction.box_index()];
+        let index = lenses.iter().position(|l| l.label == instruction.label());
+        match instruction {
+            Instruction::Set(lens) => match index {
+                Some(index) => lenses[index] = lens.clone(),
+                None => lenses.push(lens.clone()),
+            },
+            Instruction::Remove(_) => {
+                if let Some(index) = index {
+                    drop(lenses.remove(index))
                 }
             }
-        });
+        }
+    });
     boxes
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let input = parse_input(&lines[0]);
-    let hashes = input.iter().map(|s| hash(s)).collect::<Vec<_>>();
-    println!("{}", hashes.iter().map(|&h| h as u32).sum::<u32>());
-    let instructions = input
-        .iter()
-        .map(|s| parse_instruction(&s))
-        .collect::<Vec<_>>();
-    let boxes = apply_instructions(instructions);
-    println!(
-        "{}",
-        boxes
+    common::init_tracing();
+    let steps = info_span!("parse").in_scope(|| {
+        common::model::load_or_exit(|| {
+            parse_input(&read_lines("input.txt")[0])
+                .iter()
+                .map(|s| parse_step(s))
+                .collect::<Vec<_>>()
+        })
+    });
+    info_span!("part1").in_scope(|| {
+        println!("{}", hash_sum(&steps));
+    });
+    info_span!("part2").in_scope(|| {
+        let boxes = apply_instructions(steps.into_iter().map(|s| s.instruction).collect());
+        println!(
+            "{}",
+            boxes
+                .iter()
+                .enumerate()
+                .map(|(b, ls)| (b + 1)
+                    * ls.iter()
+                        .enumerate()
+                        .map(|(i, l)| (i + 1) * l.focal as usize)
+                        .sum::<usize>())
+                .sum::<usize>()
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::{dump, from_str, Format};
+
+    const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    #[test]
+    fn hashes_the_steps_as_written() {
+        let steps = parse_input(EXAMPLE)
             .iter()
-            .enumerate()
-            .map(|(b, ls)| (b + 1)
-                * ls.iter()
-                    .enumerate()
-                    .map(|(i, l)| (i + 1) * l.focal as usize)
-                    .sum::<usize>())
-            .sum::<usize>()
-    );
+            .map(|s| parse_step(s))
+            .collect::<Vec<_>>();
+        assert_eq!(hash_sum(&steps), 1320);
+        // The same lens, but not the same text
+        let padded = parse_step("qp=03");
+        assert_eq!(padded.instruction, parse_instruction("qp=3"));
+        assert_eq!(hash_sum(&[padded]), hash("qp=03") as u32);
+        assert_ne!(hash("qp=03"), hash("qp=3"));
+    }
+
+    #[test]
+    fn model_round_trip() {
+        let model = parse_input(EXAMPLE)
+            .iter()
+            .map(|s| parse_step(s))
+            .collect::<Vec<_>>();
+        assert_eq!(model[1].instruction, Instruction::Remove("cm".to_string()));
+        for format in [Format::Json, Format::Ron] {
+            let text = dump(&model, format).unwrap();
+            assert_eq!(
+                from_str::<Vec<Step>>(&text, format),
+                Ok(model.clone()),
+                "{}",
+                text
+            );
+        }
+    }
 }
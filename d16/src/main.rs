@@ -1,5 +1,8 @@
+use common::Counter;
+use grid_cell::GridCell;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,10 +11,15 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
 enum Grid {
+    #[cell('.')]
     Empty,
+    #[cell('/', false)]
+    #[cell('\\', true)]
     Mirror(bool),
+    #[cell('|', false)]
+    #[cell('-', true)]
     Splitter(bool),
 }
 
@@ -23,22 +31,8 @@ enum Direction {
     Right,
 }
 
-fn parse_input(lines: &Vec<String>) -> Vec<Vec<Grid>> {
-    lines
-        .iter()
-        .map(|l| {
-            l.chars()
-                .map(|ch| match ch {
-                    '.' => Grid::Empty,
-                    '/' => Grid::Mirror(false),
-                    '\\' => Grid::Mirror(true),
-                    '|' => Grid::Splitter(false),
-                    '-' => Grid::Splitter(true),
-                    _ => panic!("Unknown symbol: {ch}"),
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
+fn parse_input(lines: &[String]) -> Vec<Vec<Grid>> {
+    grid_cell::parse_grid(lines).unwrap()
 }
 
 struct Beam {
@@ -55,15 +49,17 @@ fn convert_direction(dir: Direction) -> (isize, isize) {
     }
 }
 
-fn traverse(map: &Vec<Vec<Grid>>, pos: (usize, usize), dir: Direction) -> Vec<Vec<bool>> {
+static BEAMS: Counter = Counter::new("beams");
+
+fn traverse(map: &[Vec<Grid>], pos: (usize, usize), dir: Direction) -> Vec<Vec<bool>> {
     let mut visited = vec![vec![vec![false; 4]; map[0].len()]; map.len()];
     let mut beams = vec![Beam {
         position: pos,
         direction: dir,
     }];
     visited[pos.0][pos.1][dir as usize] = true;
-    while !beams.is_empty() {
-        let beam = beams.pop().unwrap();
+    while let Some(beam) = beams.pop() {
+        BEAMS.incr();
         let (r, c) = beam.position;
         let new_directions = match map[r][c] {
             Grid::Empty => vec![beam.direction],
@@ -104,7 +100,25 @@ fn traverse(map: &Vec<Vec<Grid>>, pos: (usize, usize), dir: Direction) -> Vec<Ve
         .collect()
 }
 
-fn trials(map: &Vec<Vec<Grid>>) -> usize {
+fn render_energized(map: &[Vec<Grid>], energized_map: &[Vec<bool>]) -> String {
+    // One unit square per tile, energized tiles in yellow and mirrors/splitters on top
+    let cells = |f: &dyn Fn(usize, usize) -> bool| {
+        (0..map.len())
+            .flat_map(|r| (0..map[0].len()).map(move |c| (r, c)))
+            .filter(|&(r, c)| f(r, c))
+            .map(|(r, c)| format!("M{} {}h1v1h-1z", c, r))
+            .collect::<String>()
+    };
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}"><rect width="100%" height="100%" fill="#223"/><path d="{}" fill="#fd4"/><path d="{}" fill="#68a" fill-opacity="0.7"/></svg>"##,
+        map[0].len(),
+        map.len(),
+        cells(&|r, c| energized_map[r][c]),
+        cells(&|r, c| map[r][c] != Grid::Empty)
+    )
+}
+
+fn trials(map: &[Vec<Grid>]) -> usize {
     let mut max_energized = 0;
     let (nr, nc) = (map.len(), map[0].len());
     for (rows, cols, d) in [
@@ -113,8 +127,8 @@ fn trials(map: &Vec<Vec<Grid>>) -> usize {
         (Vec::from_iter(0..nr), vec![0; nr], Direction::Right),
         (Vec::from_iter(0..nr), vec![nc - 1; nr], Direction::Left),
     ] {
-        for (r, c) in rows.into_iter().zip(cols.into_iter()) {
-            let energized_map = traverse(&map, (r, c), d);
+        for (r, c) in rows.into_iter().zip(cols) {
+            let energized_map = traverse(map, (r, c), d);
             max_energized = max_energized.max(
                 energized_map
                     .iter()
@@ -127,15 +141,22 @@ fn trials(map: &Vec<Vec<Grid>>) -> usize {
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let map = parse_input(&lines);
-    let energized_map = traverse(&map, (0, 0), Direction::Right);
-    println!(
-        "{}",
-        energized_map
-            .iter()
-            .map(|r| r.iter().filter(|c| **c).count())
-            .sum::<usize>()
-    );
-    println!("{}", trials(&map));
+    common::init_tracing();
+    let map = info_span!("parse").in_scope(|| parse_input(&read_lines("input.txt")));
+    info_span!("part1").in_scope(|| {
+        let energized_map = traverse(&map, (0, 0), Direction::Right);
+        common::visualise("energized", || render_energized(&map, &energized_map));
+        println!(
+            "{}",
+            energized_map
+                .iter()
+                .map(|r| r.iter().filter(|c| **c).count())
+                .sum::<usize>()
+        );
+        BEAMS.report();
+    });
+    info_span!("part2").in_scope(|| {
+        println!("{}", trials(&map));
+        BEAMS.report();
+    });
 }
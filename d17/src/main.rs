@@ -1,7 +1,9 @@
+use common::Counter;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashSet};
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -27,9 +29,9 @@ fn convert_direction(dir: Direction) -> (isize, isize) {
     }
 }
 
-fn parse_input(lines: &Vec<String>) -> Vec<Vec<usize>> {
+fn parse_input(lines: &[String]) -> Vec<Vec<usize>> {
     lines
-        .into_iter()
+        .iter()
         .map(|line| {
             line.chars()
                 .map(|c| c.to_string().parse::<usize>().unwrap())
@@ -58,12 +60,12 @@ impl PartialOrd for State {
 impl State {
     fn get_possible_next_state(
         &self,
-        map: &Vec<Vec<usize>>,
+        map: &[Vec<usize>],
         min_steps: usize,
         max_steps: usize,
     ) -> Vec<State> {
         let mut next_states = vec![];
-        for dir in vec![
+        for dir in [
             Direction::Left,
             Direction::Up,
             Direction::Right,
@@ -102,7 +104,9 @@ impl State {
     }
 }
 
-fn minimize_heat_loss(map: &Vec<Vec<usize>>, min_steps: usize, max_steps: usize) -> Option<usize> {
+static HEAP_POPS: Counter = Counter::new("heap_pops");
+
+fn minimize_heat_loss(map: &[Vec<usize>], min_steps: usize, max_steps: usize) -> Option<usize> {
     let mut visited = HashSet::new();
     let mut heap = BinaryHeap::new();
     heap.push(State {
@@ -118,6 +122,7 @@ fn minimize_heat_loss(map: &Vec<Vec<usize>>, min_steps: usize, max_steps: usize)
         heat_loss: 0,
     });
     while let Some(state) = heap.pop() {
+        HEAP_POPS.incr();
         let ((r, c), heat_loss, count) = (state.pos, state.heat_loss, state.count);
         if (r, c) == (map.len() - 1, map[0].len() - 1) && count >= min_steps {
             return Some(heat_loss);
@@ -137,10 +142,16 @@ fn minimize_heat_loss(map: &Vec<Vec<usize>>, min_steps: usize, max_steps: usize)
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let map = parse_input(&lines);
-    let result = minimize_heat_loss(&map, 1, 3);
-    println!("{:?}", result);
-    let result = minimize_heat_loss(&map, 4, 10);
-    println!("{:?}", result);
+    common::init_tracing();
+    let map = info_span!("parse").in_scope(|| parse_input(&read_lines("input.txt")));
+    info_span!("part1").in_scope(|| {
+        let result = minimize_heat_loss(&map, 1, 3);
+        println!("{:?}", result);
+        HEAP_POPS.report();
+    });
+    info_span!("part2").in_scope(|| {
+        let result = minimize_heat_loss(&map, 4, 10);
+        println!("{:?}", result);
+        HEAP_POPS.report();
+    });
 }
@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -16,7 +17,7 @@ enum Direction {
     Down,
 }
 
-fn parse_line(line: &String, color: bool) -> (Direction, usize) {
+fn parse_line(line: &str, color: bool) -> (Direction, usize) {
     let parts = line.split_whitespace().collect::<Vec<_>>();
     assert!(parts.len() == 3);
     if !color {
@@ -47,10 +48,10 @@ fn parse_line(line: &String, color: bool) -> (Direction, usize) {
     }
 }
 
-fn calculate_area(instructions: &Vec<(Direction, usize)>) -> usize {
+fn calculate_area(instructions: &[(Direction, usize)]) -> usize {
     let mut positions = vec![(0, 0)];
     let mut length = 0;
-    instructions.into_iter().for_each(|(dir, len)| {
+    instructions.iter().for_each(|(dir, len)| {
         length += len;
         let len = *len as i64;
         let (r, c) = positions.last().unwrap().to_owned();
@@ -71,12 +72,37 @@ fn calculate_area(instructions: &Vec<(Direction, usize)>) -> usize {
         .sum::<i64>()
         .abs()
         / 2;
+    common::visualise("lagoon", || {
+        let (rmin, rmax) = (
+            positions.iter().map(|p| p.0).min().unwrap(),
+            positions.iter().map(|p| p.0).max().unwrap(),
+        );
+        let (cmin, cmax) = (
+            positions.iter().map(|p| p.1).min().unwrap(),
+            positions.iter().map(|p| p.1).max().unwrap(),
+        );
+        let points = positions
+            .iter()
+            .map(|(r, c)| format!("{},{}", c, r))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}"><polygon points="{}" fill="#8bd" stroke="#246" stroke-width="1" vector-effect="non-scaling-stroke"/></svg>"##,
+            cmin,
+            rmin,
+            cmax - cmin + 1,
+            rmax - rmin + 1,
+            points
+        )
+    });
     area as usize + length / 2 + 1
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    for color in [false, true] {
+    common::init_tracing();
+    let lines = info_span!("parse").in_scope(|| read_lines("input.txt"));
+    for (color, span) in [(false, info_span!("part1")), (true, info_span!("part2"))] {
+        let _guard = span.enter();
         let instructions = lines
             .iter()
             .map(|l| parse_line(l, color))
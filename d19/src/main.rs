@@ -1,7 +1,10 @@
+use interval::{Cuboid, Interval};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -10,7 +13,7 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 enum Category {
     X,
     M,
@@ -18,14 +21,14 @@ enum Category {
     S,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 enum Status {
     Accept,
     Reject,
     Next(String),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 struct Part {
     values: [usize; 4],
 }
@@ -43,12 +46,40 @@ impl Part {
     }
 }
 
-fn parse_input(
-    lines: &Vec<String>,
-) -> (
-    HashMap<String, Vec<(Option<(Category, Ordering, usize)>, Status)>>,
-    Vec<Part>,
-) {
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+struct Condition {
+    category: Category,
+    #[serde(with = "comparison")]
+    order: Ordering,
+    threshold: usize,
+}
+
+mod comparison {
+    // Orderings are written as the `<` and `>` of the input format
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::cmp::Ordering;
+
+    pub fn serialize<S: Serializer>(order: &Ordering, serializer: S) -> Result<S::Ok, S::Error> {
+        match order {
+            Ordering::Less => serializer.serialize_str("<"),
+            Ordering::Greater => serializer.serialize_str(">"),
+            Ordering::Equal => Err(serde::ser::Error::custom("Invalid order")),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ordering, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "<" => Ok(Ordering::Less),
+            ">" => Ok(Ordering::Greater),
+            s => Err(D::Error::custom(format!("Invalid order: {}", s))),
+        }
+    }
+}
+
+type Workflows = BTreeMap<String, Vec<(Option<Condition>, Status)>>;
+
+fn parse_input(lines: &[String]) -> (Workflows, Vec<Part>) {
     let parts = lines.split(|s| s.is_empty()).collect::<Vec<_>>();
     assert!(parts.len() == 2);
     let workflows = parts[0]
@@ -90,7 +121,14 @@ fn parse_input(
                                     _ => panic!("Invalid order"),
                                 };
                                 let threshold = parts[1].parse::<usize>().unwrap();
-                                return (Some((category, order, threshold)), target);
+                                return (
+                                    Some(Condition {
+                                        category,
+                                        order,
+                                        threshold,
+                                    }),
+                                    target,
+                                );
                             }
                         }
                         panic!("Invalid rule");
@@ -108,7 +146,7 @@ fn parse_input(
             (name, rules)
         })
         .collect::<Vec<_>>();
-    let mut rules = HashMap::new();
+    let mut rules = BTreeMap::new();
     for (name, r) in workflows {
         rules.insert(name, r);
     }
@@ -143,16 +181,17 @@ fn parse_input(
     (rules, parts)
 }
 
-fn match_rules(
-    part: &Part,
-    rules: &HashMap<String, Vec<(Option<(Category, Ordering, usize)>, Status)>>,
-) -> bool {
+fn match_rules(part: &Part, rules: &Workflows) -> bool {
     let mut name = "in".to_string();
     loop {
         let rule = rules.get(&name).unwrap();
         for (r, s) in rule {
             match r {
-                Some((category, order, threshold)) => {
+                Some(Condition {
+                    category,
+                    order,
+                    threshold,
+                }) => {
                     if part.get(*category).cmp(threshold) == *order {
                         match s {
                             Status::Next(next) => {
@@ -177,67 +216,46 @@ fn match_rules(
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct PartRange {
-    pub min: [usize; 4],
-    pub max: [usize; 4],
+/// The parts whose ratings are all within a range, one axis per category
+type PartRange = Cuboid<4>;
+
+fn all_parts() -> PartRange {
+    Cuboid::new([Interval::new(1, 4001); 4])
 }
-impl PartRange {
-    fn new() -> PartRange {
-        PartRange {
-            min: [1; 4],
-            max: [4000; 4],
-        }
-    }
-    fn split(
-        &self,
-        category: Category,
-        value: usize,
-        order: Ordering,
-    ) -> (Option<PartRange>, Option<PartRange>) {
-        let mut matched = None;
-        let mut unmatched = None;
-        let (mut min, mut max) = (self.min, self.max);
-        match order {
-            Ordering::Greater => {
-                min[category as usize] = value + 1;
-                if min[category as usize] <= self.max[category as usize] {
-                    matched = Some(PartRange { min, max: self.max });
-                }
-                max[category as usize] = value;
-                if self.min[category as usize] <= max[category as usize] {
-                    unmatched = Some(PartRange { min: self.min, max });
-                }
-            }
-            Ordering::Less => {
-                max[category as usize] = value - 1;
-                if self.min[category as usize] <= max[category as usize] {
-                    matched = Some(PartRange { min: self.min, max });
-                }
-                min[category as usize] = value;
-                if min[category as usize] <= self.max[category as usize] {
-                    unmatched = Some(PartRange { min, max: self.max });
-                }
-            }
-            _ => panic!("Invalid order"),
+
+/// The parts that satisfy the condition, and the ones that don't
+fn split(
+    part: &PartRange,
+    category: Category,
+    value: usize,
+    order: Ordering,
+) -> (Option<PartRange>, Option<PartRange>) {
+    let axis = category as usize;
+    let value = value as u64;
+    match order {
+        Ordering::Greater => {
+            let (unmatched, matched) = part.split(axis, value + 1);
+            (matched, unmatched)
         }
-        (matched, unmatched)
+        Ordering::Less => part.split(axis, value),
+        _ => panic!("Invalid order"),
     }
 }
 
-fn match_range_rules(
-    rules: &HashMap<String, Vec<(Option<(Category, Ordering, usize)>, Status)>>,
-) -> Vec<PartRange> {
-    let mut queue = vec![("in".to_string(), PartRange::new())];
+fn match_range_rules(rules: &Workflows) -> Vec<PartRange> {
+    let mut queue = vec![("in".to_string(), all_parts())];
     let mut matches = Vec::new();
-    while !queue.is_empty() {
-        let (name, part) = queue.pop().unwrap();
+    while let Some((name, part)) = queue.pop() {
         let mut part = part;
         let rule = rules.get(&name).unwrap();
         for (r, s) in rule {
             match r {
-                Some((category, order, threshold)) => {
-                    let (matched, unmatched) = part.split(*category, *threshold, *order);
+                Some(Condition {
+                    category,
+                    order,
+                    threshold,
+                }) => {
+                    let (matched, unmatched) = split(&part, *category, *threshold, *order);
                     if let Some(matched) = matched {
                         match s {
                             Status::Next(next) => {
@@ -268,20 +286,73 @@ fn match_range_rules(
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let (rules, parts) = parse_input(&lines);
-    let result = parts
-        .iter()
-        .filter(|&p| match_rules(p, &rules))
-        .map(|p| p.values.iter().sum::<usize>())
-        .sum::<usize>();
-    println!("{}", result);
-    let ranges = match_range_rules(&rules);
-    println!(
-        "{}",
-        ranges
-            .into_iter()
-            .map(|r| (0..4).map(|i| r.max[i] - r.min[i] + 1).product::<usize>())
-            .sum::<usize>()
-    );
+    common::init_tracing();
+    let (rules, parts) = info_span!("parse")
+        .in_scope(|| common::model::load_or_exit(|| parse_input(&read_lines("input.txt"))));
+    info_span!("part1").in_scope(|| {
+        let result = parts
+            .iter()
+            .filter(|&p| match_rules(p, &rules))
+            .map(|p| p.values.iter().sum::<usize>())
+            .sum::<usize>();
+        println!("{}", result);
+    });
+    info_span!("part2").in_scope(|| {
+        let ranges = match_range_rules(&rules);
+        println!("{}", ranges.into_iter().map(|r| r.volume()).sum::<u128>());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::{dump, from_str, Format};
+
+    const EXAMPLE: [&str; 5] = [
+        "px{a<2006:qkq,m>2090:A,rfg}",
+        "qkq{x<1416:A,crn}",
+        "",
+        "{x=787,m=2655,a=1222,s=2876}",
+        "{x=1679,m=44,a=2067,s=496}",
+    ];
+
+    #[test]
+    fn model_round_trip() {
+        let model = parse_input(&EXAMPLE.map(|l| l.to_string()));
+        for format in [Format::Json, Format::Ron] {
+            let text = dump(&model, format).unwrap();
+            assert_eq!(
+                from_str::<(Workflows, Vec<Part>)>(&text, format),
+                Ok(model.clone()),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn comparison() {
+        let condition = Condition {
+            category: Category::A,
+            order: Ordering::Less,
+            threshold: 2006,
+        };
+        let json = dump(&condition, Format::Json).unwrap();
+        assert!(json.contains(r#""order": "<""#), "{}", json);
+        let greater = json.replace(r#""<""#, r#"">""#);
+        assert_eq!(
+            from_str::<Condition>(&greater, Format::Json).map(|c| c.order),
+            Ok(Ordering::Greater)
+        );
+        let equal = json.replace(r#""<""#, r#""=""#);
+        let error = from_str::<Condition>(&equal, Format::Json).unwrap_err();
+        assert!(error.contains("Invalid order: ="), "{}", error);
+        let condition = Condition {
+            order: Ordering::Equal,
+            ..condition
+        };
+        assert!(dump(&condition, Format::Ron)
+            .unwrap_err()
+            .contains("Invalid order"));
+    }
 }
@@ -0,0 +1,214 @@
+use crate::Bag;
+
+/// Largest number of cubes of a colour the estimator considers
+const MAX_CUBES: u64 = 1000;
+
+/// Log-likelihoods closer than this are ties, as sums of logs of factorials aren't exact
+const TIE: f64 = 1e-10;
+
+/// Half the 95% quantile of the chi-squared distribution with one degree of freedom
+const LOG_LIKELIHOOD_DROP: f64 = 3.841 / 2.0;
+
+/// Estimated number of cubes of one colour, with a conditional 95% interval: the counts of
+/// this colour the likelihood-ratio test doesn't reject while the other colours stay at their
+/// estimate. It doesn't account for the uncertainty of the other colours, so it's narrower
+/// than a profile interval, which would maximise over them for every count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Estimate {
+    pub color: String,
+    /// `None` when the likelihood keeps growing with the number of cubes of this colour, i.e.
+    /// the draws are as likely as can be from a bag so large that drawing doesn't deplete it
+    pub count: Option<u64>,
+    pub low: u64,
+    /// `None` when the draws can't rule out any larger number of cubes
+    pub high: Option<u64>,
+}
+
+/// Logs of the factorials up to the largest bag considered
+struct LnFactorials(Vec<f64>);
+
+impl LnFactorials {
+    fn new(max: u64) -> LnFactorials {
+        let mut table = vec![0.0; max as usize + 1];
+        for n in 1..=max as usize {
+            table[n] = table[n - 1] + (n as f64).ln();
+        }
+        LnFactorials(table)
+    }
+
+    /// Log of the number of ways to pick `k` out of `n`
+    fn choose(&self, n: u64, k: u64) -> f64 {
+        if k > n {
+            return f64::NEG_INFINITY;
+        }
+        self.0[n as usize] - self.0[k as usize] - self.0[(n - k) as usize]
+    }
+
+    /// Log-likelihood of the draws for a bag with `counts` cubes of every colour. Each set is
+    /// drawn in one go, without replacement, and put back in the bag before the next one.
+    fn likelihood(&self, sets: &[Vec<u64>], counts: &[u64]) -> f64 {
+        let total = counts.iter().sum::<u64>();
+        sets.iter()
+            .map(|set| {
+                set.iter()
+                    .zip(counts)
+                    .map(|(&drawn, &n)| self.choose(n, drawn))
+                    .sum::<f64>()
+                    - self.choose(total, set.iter().sum())
+            })
+            .sum()
+    }
+
+    /// Log-likelihood of every count of one colour from `min`, with the other colours fixed
+    fn profile(&self, sets: &[Vec<u64>], counts: &[u64], i: usize, min: u64) -> Vec<(u64, f64)> {
+        let mut counts = counts.to_vec();
+        (min..=MAX_CUBES)
+            .map(|n| {
+                counts[i] = n;
+                (n, self.likelihood(sets, &counts))
+            })
+            .collect()
+    }
+}
+
+/// Maximum-likelihood number of cubes of each colour of `palette` given a game's sets.
+///
+/// The search starts from the best bag holding the colours in the proportions they were drawn
+/// in, then improves one colour at a time until none changes, taking the smallest count on
+/// ties. A colour that reaches the largest count considered has no estimate, and the
+/// others are estimated with it at that count.
+pub fn estimate(sets: &[Bag], palette: &[String]) -> Vec<Estimate> {
+    let ln = LnFactorials::new(MAX_CUBES * palette.len() as u64);
+    let draws = sets
+        .iter()
+        .map(|set| palette.iter().map(|color| set.get(color)).collect())
+        .collect::<Vec<Vec<u64>>>();
+    // There are at least as many cubes as were ever drawn at once
+    let min = (0..palette.len())
+        .map(|i| draws.iter().map(|set| set[i]).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    // Growing one colour alone can't find draws that look like they came from an endless bag,
+    // so start from the best bag in the proportions the colours were drawn in
+    let drawn = (0..palette.len())
+        .map(|i| draws.iter().map(|set| set[i]).sum::<u64>())
+        .collect::<Vec<_>>();
+    let total = drawn.iter().sum::<u64>().max(1);
+    let scaled = |size: u64| {
+        (0..palette.len())
+            .map(|i| (size * drawn[i]).div_ceil(total).clamp(min[i], MAX_CUBES))
+            .collect::<Vec<_>>()
+    };
+    let mut counts = (0..=MAX_CUBES * palette.len() as u64)
+        .map(scaled)
+        .fold((min.clone(), f64::NEG_INFINITY), |best, counts| {
+            let l = ln.likelihood(&draws, &counts);
+            if l > best.1 + TIE {
+                (counts, l)
+            } else {
+                best
+            }
+        })
+        .0;
+    loop {
+        let mut changed = false;
+        for i in 0..palette.len() {
+            let (best, _) = ln.profile(&draws, &counts, i, min[i]).into_iter().fold(
+                (min[i], f64::NEG_INFINITY),
+                |best, (n, l)| if l > best.1 + TIE { (n, l) } else { best },
+            );
+            if best != counts[i] {
+                counts[i] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let best = ln.likelihood(&draws, &counts);
+    palette
+        .iter()
+        .enumerate()
+        .map(|(i, color)| {
+            if counts[i] >= MAX_CUBES {
+                return Estimate {
+                    color: color.clone(),
+                    count: None,
+                    low: min[i],
+                    high: None,
+                };
+            }
+            let plausible = ln
+                .profile(&draws, &counts, i, min[i])
+                .into_iter()
+                .filter(|&(_, l)| best - l <= LOG_LIKELIHOOD_DROP)
+                .map(|(n, _)| n)
+                .collect::<Vec<_>>();
+            let high = *plausible.last().unwrap();
+            Estimate {
+                color: color.clone(),
+                count: Some(counts[i]),
+                low: plausible[0],
+                high: (high < MAX_CUBES).then_some(high),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(colors: &[&str]) -> Vec<String> {
+        colors.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn drawing_everything() {
+        // A single set is most likely when it emptied the bag
+        let sets = [Bag::from([("red", 3), ("blue", 2)])];
+        let estimates = estimate(&sets, &palette(&["red", "blue"]));
+        assert_eq!(estimates[0].count, Some(3));
+        assert_eq!(estimates[1].count, Some(2));
+        assert_eq!(estimates[0].low, 3);
+    }
+
+    #[test]
+    fn closed_form() {
+        // One red, then one blue: the likelihood is R B / (R + B)^2, highest when R = B, and
+        // the smallest such bag wins the tie
+        let sets = [Bag::from([("red", 1)]), Bag::from([("blue", 1)])];
+        let estimates = estimate(&sets, &palette(&["red", "blue"]));
+        assert_eq!(estimates[0].count, Some(1));
+        assert_eq!(estimates[1].count, Some(1));
+        // With blue at 1, red is kept while R / (R + 1)^2 is within the drop of 1 / 4
+        let likelihood = |r: f64| (r / (r + 1.0).powi(2)).ln();
+        let high = (1..MAX_CUBES)
+            .take_while(|&r| likelihood(1.0) - likelihood(r as f64) <= LOG_LIKELIHOOD_DROP)
+            .last()
+            .unwrap();
+        assert_eq!(high, 25);
+        assert_eq!(estimates[0].low, 1);
+        assert_eq!(estimates[0].high, Some(high));
+        assert_eq!(estimates[1].high, Some(high));
+    }
+
+    #[test]
+    fn one_colour_unbounded() {
+        // These draws look like drawing with replacement from an endless bag of red and blue,
+        // while a bag with any green would have shown some
+        let sets = [
+            Bag::from([("red", 2)]),
+            Bag::from([("blue", 2)]),
+            Bag::from([("red", 1), ("blue", 1)]),
+            Bag::from([("red", 1), ("blue", 1)]),
+        ];
+        let estimates = estimate(&sets, &palette(&["red", "blue", "green"]));
+        for e in estimates[..2].iter() {
+            assert_eq!((e.count, e.low, e.high), (None, 2, None), "{}", e.color);
+        }
+        assert_eq!(estimates[2].count, Some(0));
+        assert_eq!(estimates[2].low, 0);
+        assert!(estimates[2].high.is_some());
+    }
+}
@@ -1,58 +1,107 @@
+mod estimate;
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
-use std::str::FromStr;
-use strum::EnumString;
-
-#[derive(Debug, PartialEq, EnumString)]
-#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
-enum Color {
-    Red,
-    Green,
-    Blue,
-}
+use tracing::info_span;
 
-#[derive(Debug, Eq, Ord)]
+/// Highest count seen of every colour, by colour name
+#[derive(Debug, Clone, Eq)]
 struct Bag {
-    red: u64,
-    green: u64,
-    blue: u64,
+    cubes: BTreeMap<String, u64>,
 }
 
 impl Bag {
     fn new() -> Bag {
         Bag {
-            red: 0,
-            green: 0,
-            blue: 0,
+            cubes: BTreeMap::new(),
         }
     }
 
-    fn update(&mut self, color: Color, value: u64) {
-        match color {
-            Color::Red => self.red = value.max(self.red),
-            Color::Green => self.green = value.max(self.green),
-            Color::Blue => self.blue = value.max(self.blue),
-        }
+    /// Colours are kept lower-cased, so "Red" and "red" are the same colour
+    fn key(color: &str) -> String {
+        color.to_lowercase()
+    }
+
+    fn update(&mut self, color: &str, value: u64) {
+        let count = self.cubes.entry(Bag::key(color)).or_insert(0);
+        *count = value.max(*count);
+    }
+
+    fn get(&self, color: &str) -> u64 {
+        self.cubes.get(&Bag::key(color)).copied().unwrap_or(0)
+    }
+
+    /// Also list the colours in `palette` this bag has none of
+    fn include(&mut self, palette: &BTreeSet<String>) {
+        palette.iter().for_each(|color| self.update(color, 0));
     }
 
     fn power(&self) -> u64 {
-        self.red * self.green * self.blue
+        self.cubes.values().product()
+    }
+
+    /// Whether every colour is within `limit`, which has none of the colours it does not list
+    fn feasible(&self, limit: &Bag) -> bool {
+        self <= limit
+    }
+
+    /// Smallest bag holding both bags
+    fn join(&self, other: &Bag) -> Bag {
+        let mut bag = self.clone();
+        other
+            .cubes
+            .iter()
+            .for_each(|(color, &n)| bag.update(color, n));
+        bag
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cubes = self
+            .cubes
+            .iter()
+            .map(|(color, n)| format!("{} {}", n, color))
+            .collect::<Vec<_>>();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl<const N: usize> From<[(&str, u64); N]> for Bag {
+    fn from(cubes: [(&str, u64); N]) -> Bag {
+        let mut bag = Bag::new();
+        cubes
+            .into_iter()
+            .for_each(|(color, n)| bag.update(color, n));
+        bag
     }
 }
 
 impl PartialEq for Bag {
     fn eq(&self, other: &Self) -> bool {
-        self.red == other.red && self.green == other.green && self.blue == other.blue
+        self.cubes
+            .keys()
+            .chain(other.cubes.keys())
+            .all(|color| self.get(color) == other.get(color))
     }
 }
 
+/// A bag is below another when it has no more cubes of any colour. Bags with more of one
+/// colour and less of another are incomparable.
 impl PartialOrd for Bag {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self.red > other.red || self.green > other.green || self.blue > other.blue {
-            Some(std::cmp::Ordering::Greater)
-        } else {
-            Some(std::cmp::Ordering::Less)
-        }
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let mut colors = self.cubes.keys().chain(other.cubes.keys());
+        colors.try_fold(Ordering::Equal, |order, color| {
+            match (order, self.get(color).cmp(&other.get(color))) {
+                (order, Ordering::Equal) => Some(order),
+                (Ordering::Equal, cmp) => Some(cmp),
+                (order, cmp) if order == cmp => Some(order),
+                _ => None,
+            }
+        })
     }
 }
 
@@ -63,52 +112,286 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-fn parse_line(line: &str) -> (u64, Bag) {
+/// Cubes of a set like `3 blue, 4 red`
+fn parse_set(set: &str) -> Bag {
     let mut bag = Bag::new();
+    for s in set.split(", ") {
+        let parts = s.split(" ").collect::<Vec<&str>>();
+        assert!(parts.len() == 2);
+        let value = parts[0].parse::<u64>().unwrap();
+        bag.update(parts[1], value);
+    }
+    bag
+}
+
+/// Id and sets of a game
+fn parse_line(line: &str) -> (u64, Vec<Bag>) {
     let parts: Vec<&str> = line.split(": ").collect();
     assert!(parts.len() == 2);
     let id = parts[0].split(" ").last().unwrap().parse::<u64>().unwrap();
-    let sets = parts[1]
-        .split("; ")
-        .map(|s| {
-            s.split(", ")
-                .map(|s| {
-                    let parts = s.split(" ").collect::<Vec<&str>>();
-                    assert!(parts.len() == 2);
-                    let color = Color::from_str(parts[1]).unwrap();
-                    let value = parts[0].parse::<u64>().unwrap();
-                    (color, value)
+    let sets = parts[1].split("; ").map(parse_set).collect::<Vec<Bag>>();
+    (id, sets)
+}
+
+/// Ids of the games that could have been played with `bag`
+fn feasible_games(games: &[(u64, Bag)], bag: &Bag) -> Vec<u64> {
+    games
+        .iter()
+        .filter(|(_, game)| game.feasible(bag))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// Smallest bag that could have played all of `games`
+fn covering_bag<'a>(games: impl IntoIterator<Item = &'a Bag>) -> Bag {
+    games
+        .into_iter()
+        .fold(Bag::new(), |bag, game| bag.join(game))
+}
+
+/// Smallest bag that could have played the games of a comma separated list of ids
+fn cover(bags: &[(u64, Bag)], ids: &str) -> Result<Bag, String> {
+    let mut cover = Bag::new();
+    for id in ids.split(",").map(|id| id.trim()) {
+        let bag = id
+            .parse::<u64>()
+            .ok()
+            .and_then(|id| bags.iter().find(|(i, _)| *i == id))
+            .ok_or_else(|| format!("No game {}", id))?;
+        cover = cover.join(&bag.1);
+    }
+    Ok(cover)
+}
+
+/// Games whose minimal bag is not below another game's, which alone decide the covering bag
+fn pareto_frontier(games: &[(u64, Bag)]) -> Vec<u64> {
+    games
+        .iter()
+        .filter(|(_, bag)| !games.iter().any(|(_, other)| bag < other))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// A colour drawn in a set more often than the bag holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation {
+    /// Starting at 1, in the order of the game log
+    set: usize,
+    color: String,
+    drawn: u64,
+    excess: u64,
+}
+
+fn violations(sets: &[Bag], limit: &Bag) -> Vec<Violation> {
+    sets.iter()
+        .enumerate()
+        .flat_map(|(i, set)| {
+            set.cubes
+                .iter()
+                .filter(|(color, &n)| n > limit.get(color))
+                .map(move |(color, &n)| Violation {
+                    set: i + 1,
+                    color: color.clone(),
+                    drawn: n,
+                    excess: n - limit.get(color),
                 })
-                .collect::<Vec<(Color, u64)>>()
         })
-        .collect::<Vec<Vec<(Color, u64)>>>();
-    for set in sets {
-        for (color, value) in set {
-            bag.update(color, value);
+        .collect()
+}
+
+fn list(ids: &[u64]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn main() {
+    common::init_tracing();
+    let target = Bag::from([("red", 12), ("green", 13), ("blue", 14)]);
+    let (games, bags, palette) = info_span!("parse").in_scope(|| {
+        let lines = read_lines("input.txt");
+        let games = lines
+            .iter()
+            .map(|l| parse_line(l))
+            .collect::<Vec<(u64, Vec<Bag>)>>();
+        let mut bags = games
+            .iter()
+            .map(|(id, sets)| (*id, covering_bag(sets)))
+            .collect::<Vec<(u64, Bag)>>();
+        // A game without some colour still has a bag with none of it, which matters for the power
+        let palette = bags
+            .iter()
+            .flat_map(|(_, bag)| bag.cubes.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        bags.iter_mut().for_each(|(_, bag)| bag.include(&palette));
+        (games, bags, palette)
+    });
+    // Queries answered instead of the puzzle
+    if let Some(set) = common::flag_value("--feasible") {
+        println!("{}", list(&feasible_games(&bags, &parse_set(&set))));
+        return;
+    }
+    if common::has_flag("--violations") {
+        for (id, sets) in games.iter() {
+            for v in violations(sets, &target) {
+                println!(
+                    "Game {} set {}: {} {}, {} more than the bag holds",
+                    id, v.set, v.drawn, v.color, v.excess
+                );
+            }
         }
+        return;
     }
-    (id, bag)
+    if let Some(ids) = common::flag_value("--cover") {
+        match cover(&bags, &ids) {
+            Ok(bag) => println!("{}", bag),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if common::has_flag("--estimate") {
+        let palette = palette.into_iter().collect::<Vec<_>>();
+        for (id, sets) in games.iter() {
+            let estimates = estimate::estimate(sets, &palette)
+                .into_iter()
+                .map(|e| {
+                    let count = e.count.map(|n| n.to_string()).unwrap_or("?".to_string());
+                    let high = e.high.map(|h| h.to_string()).unwrap_or_default();
+                    format!("{} {} (conditional {}..{})", count, e.color, e.low, high)
+                })
+                .collect::<Vec<_>>();
+            println!("Game {}: {}", id, estimates.join(", "));
+        }
+        return;
+    }
+    if common::has_flag("--frontier") {
+        println!("{}", list(&pareto_frontier(&bags)));
+        return;
+    }
+    info_span!("part1").in_scope(|| {
+        let possible = bags
+            .iter()
+            .filter(|(_, bag)| bag.feasible(&target))
+            .map(|(id, _)| *id)
+            .collect::<Vec<u64>>()
+            .into_iter()
+            .sum::<u64>();
+        println!("{}", possible);
+    });
+    info_span!("part2").in_scope(|| {
+        let power = bags.iter().map(|(_, bag)| bag.power()).sum::<u64>();
+        println!("{}", power);
+    });
 }
 
-fn main() {
-    const TARGET: Bag = Bag {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
-    let lines = read_lines("input.txt");
-    let bags = lines
-        .iter()
-        .map(|l| parse_line(l))
-        .collect::<Vec<(u64, Bag)>>();
-    let possible = bags
-        .iter()
-        .filter(|(_, bag)| bag <= &TARGET)
-        .map(|(id, _)| *id)
-        .collect::<Vec<u64>>()
-        .into_iter()
-        .sum::<u64>();
-    println!("{}", possible);
-    let power = bags.iter().map(|(_, bag)| bag.power()).sum::<u64>();
-    println!("{}", power);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colours_ignore_case() {
+        let mut bag = parse_set("3 Blue, 4 red");
+        bag.update("RED", 6);
+        assert_eq!(bag.get("blue"), 3);
+        assert_eq!(bag.get("BLUE"), 3);
+        assert_eq!(bag.get("Red"), 6);
+        assert_eq!(bag.to_string(), "3 blue, 6 red");
+        assert_eq!(bag, Bag::from([("red", 6), ("blue", 3)]));
+        assert!(bag.feasible(&Bag::from([("Red", 12), ("BLUE", 14)])));
+    }
+
+    /// Every bag of red and green with at most two of each, a colour at 0 being listed or not
+    fn small_bags() -> Vec<Bag> {
+        let counts = [None, Some(0), Some(1), Some(2)];
+        let mut bags = Vec::new();
+        for red in counts {
+            for green in counts {
+                let mut bag = Bag::new();
+                for (color, n) in [("red", red), ("green", green)] {
+                    if let Some(n) = n {
+                        bag.update(color, n);
+                    }
+                }
+                bags.push(bag);
+            }
+        }
+        bags
+    }
+
+    #[test]
+    fn partial_order_laws() {
+        let bags = small_bags();
+        for a in bags.iter() {
+            assert_eq!(a.partial_cmp(a), Some(Ordering::Equal), "{}", a);
+            for b in bags.iter() {
+                let (ab, ba) = (a.partial_cmp(b), b.partial_cmp(a));
+                assert_eq!(ab, ba.map(Ordering::reverse), "{} / {}", a, b);
+                if a <= b && b <= a {
+                    assert_eq!(a, b);
+                }
+                assert_eq!(ab == Some(Ordering::Equal), a == b, "{} / {}", a, b);
+                for c in bags.iter() {
+                    if a <= b && b <= c {
+                        assert!(a <= c, "{} <= {} <= {}", a, b, c);
+                    }
+                    if a < b && b < c {
+                        assert!(a < c, "{} < {} < {}", a, b, c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn incomparable_bags() {
+        let a = Bag::from([("red", 2), ("green", 1)]);
+        let b = Bag::from([("red", 1), ("green", 2)]);
+        assert_eq!(a.partial_cmp(&b), None);
+        assert_eq!([a < b, a > b, a <= b, a >= b], [false; 4]);
+        // A colour missing from one bag counts as none of it
+        let c = Bag::from([("blue", 1)]);
+        assert_eq!(a.partial_cmp(&c), None);
+        assert_eq!(Bag::new().partial_cmp(&c), Some(Ordering::Less));
+        assert_eq!(Bag::from([("blue", 0)]), Bag::new());
+    }
+
+    #[test]
+    fn cover_ids() {
+        let bags = [
+            (1, Bag::from([("red", 4), ("blue", 6)])),
+            (3, Bag::from([("red", 20), ("green", 13)])),
+        ];
+        assert_eq!(
+            cover(&bags, "1, 3"),
+            Ok(Bag::from([("red", 20), ("green", 13), ("blue", 6)]))
+        );
+        assert_eq!(cover(&bags, "1,2"), Err("No game 2".to_string()));
+        assert_eq!(cover(&bags, "one"), Err("No game one".to_string()));
+    }
+
+    #[test]
+    fn violations_by_set() {
+        let (_, sets) =
+            parse_line("Game 4: 3 Red, 2 blue; 13 RED, 2 purple; 1 green; 12 red, 15 Blue");
+        let limit = Bag::from([("red", 12), ("green", 13), ("blue", 14)]);
+        let violation = |set, color: &str, drawn, excess| Violation {
+            set,
+            color: color.to_string(),
+            drawn,
+            excess,
+        };
+        assert_eq!(
+            violations(&sets, &limit),
+            vec![
+                violation(2, "purple", 2, 2),
+                violation(2, "red", 13, 1),
+                violation(4, "blue", 15, 1),
+            ]
+        );
+        assert_eq!(violations(&sets[2..3], &limit), vec![]);
+    }
 }
@@ -1,7 +1,10 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use common::Counter;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::Read;
+use tracing::{debug, info_span};
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -10,29 +13,29 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 enum Pulse {
     Low,
     High,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 enum Module {
     FlipFlop(bool),
     Conjunction(Vec<Pulse>),
     Broadcast,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 struct Node {
     module: Module,
     src: Vec<String>,
     dst: Vec<String>,
 }
 
-fn parse_lines(lines: &Vec<String>) -> HashMap<String, Node> {
-    let mut nodes = HashMap::new();
-    lines.into_iter().for_each(|l| {
+fn parse_lines(lines: &[String]) -> BTreeMap<String, Node> {
+    let mut nodes = BTreeMap::new();
+    lines.iter().for_each(|l| {
         let parts = l.split(" -> ").collect::<Vec<_>>();
         assert!(parts.len() == 2);
         let mut src = parts[0].to_owned();
@@ -52,7 +55,7 @@ fn parse_lines(lines: &Vec<String>) -> HashMap<String, Node> {
         nodes.insert(
             src,
             Node {
-                module: module,
+                module,
                 src: vec![],
                 dst: dst.split(", ").map(|s| s.to_owned()).collect::<Vec<_>>(),
             },
@@ -65,9 +68,8 @@ fn parse_lines(lines: &Vec<String>) -> HashMap<String, Node> {
                 .entry(d)
                 .and_modify(|node| {
                     node.src.push(k.clone());
-                    match node.module {
-                        Module::Conjunction(ref mut pulses) => pulses.push(Pulse::Low),
-                        _ => {}
+                    if let Module::Conjunction(ref mut pulses) = node.module {
+                        pulses.push(Pulse::Low)
                     }
                 })
                 .or_insert(Node {
@@ -80,19 +82,18 @@ fn parse_lines(lines: &Vec<String>) -> HashMap<String, Node> {
     nodes
 }
 
-fn push_button(
-    states: &HashMap<String, Node>,
-) -> (
-    HashMap<String, Node>,
-    Vec<(String, String, Pulse)>,
-    Vec<(String, String, Pulse)>,
-) {
+type Pulses = Vec<(String, String, Pulse)>;
+
+static PULSES: Counter = Counter::new("pulses");
+
+fn push_button(states: &BTreeMap<String, Node>) -> (BTreeMap<String, Node>, Pulses, Pulses) {
     let mut states = states.clone();
     let mut queue = VecDeque::from([("button".to_string(), "broadcaster".to_string(), Pulse::Low)]);
     let mut lows = Vec::new();
     let mut highs = Vec::new();
     while !queue.is_empty() {
         let (src, dst, pulse) = queue.pop_front().unwrap();
+        PULSES.incr();
         match pulse {
             Pulse::High => highs.push((src.clone(), dst.clone(), pulse)),
             Pulse::Low => lows.push((src.clone(), dst.clone(), pulse)),
@@ -134,48 +135,103 @@ fn push_button(
     (states, lows, highs)
 }
 
-fn get_insight(map: &HashMap<String, Node>) {
+fn render_graph(
+    map: &BTreeMap<String, Node>,
+    layers: &[Vec<String>],
+    edges: &[(String, String)],
+) -> String {
+    // Layered layout: one row per BFS depth from the broadcaster
+    let (dx, dy) = (90, 70);
+    let width = layers.iter().map(|l| l.len()).max().unwrap_or(1) * dx;
+    let mut position = HashMap::new();
+    layers.iter().enumerate().for_each(|(depth, layer)| {
+        let offset = (width - layer.len() * dx) / 2 + dx / 2;
+        layer.iter().enumerate().for_each(|(i, name)| {
+            position.insert(name.clone(), (offset + i * dx, depth * dy + dy / 2));
+        });
+    });
+    let lines = edges
+        .iter()
+        .map(|(src, dst)| {
+            let ((x1, y1), (x2, y2)) = (position[src], position[dst]);
+            format!(r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"/>"#)
+        })
+        .collect::<String>();
+    let nodes = position
+        .iter()
+        .map(|(name, (x, y))| {
+            let (prefix, color) = match map[name].module {
+                Module::FlipFlop(_) => ("%", "#fd8"),
+                Module::Conjunction(_) => ("&amp;", "#8bd"),
+                Module::Broadcast => ("", "#ddd"),
+            };
+            format!(
+                r#"<g transform="translate({x},{y})"><rect x="-32" y="-12" width="64" height="24" rx="6" fill="{color}"/><text text-anchor="middle" dy="4">{prefix}{name}</text></g>"#
+            )
+        })
+        .collect::<String>();
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" font-family="monospace" font-size="12"><g stroke="#999" stroke-opacity="0.6">{}</g>{}</svg>"##,
+        width,
+        layers.len() * dy,
+        lines,
+        nodes
+    )
+}
+
+fn get_insight(map: &BTreeMap<String, Node>) {
     // BFS
-    let mut queue = VecDeque::from(["broadcaster".to_string()]);
+    let mut queue = VecDeque::from([("broadcaster".to_string(), 0)]);
     let mut visited = HashSet::new();
     let mut edges = Vec::new();
+    let mut layers: Vec<Vec<String>> = Vec::new();
     while !queue.is_empty() {
-        let name = queue.pop_front().unwrap();
+        let (name, depth) = queue.pop_front().unwrap();
         if visited.contains(&name) {
             continue;
         }
         visited.insert(name.clone());
+        if layers.len() <= depth {
+            layers.push(Vec::new());
+        }
+        layers[depth].push(name.clone());
         let node = map.get(&name).unwrap();
         node.dst.iter().for_each(|d| {
-            queue.push_back(d.clone());
+            queue.push_back((d.clone(), depth + 1));
             edges.push((name.clone(), d.clone()));
         });
     }
-    println!(
+    common::visualise("modules", || render_graph(map, &layers, &edges));
+    debug!(
         "graph TD\n{}",
         edges
             .iter()
             .map(|(src, dst)| "    {src}-->{dst}"
-                .replace("{src}", &src)
-                .replace("{dst}", &dst))
+                .replace("{src}", src)
+                .replace("{dst}", dst))
             .collect::<Vec<_>>()
             .join("\n")
     );
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let init = parse_lines(&lines);
-    let mut map = init.clone();
-    let mut low_count = 0;
-    let mut high_count = 0;
-    for _ in 0..1_000 {
-        let (new_map, lows, highs) = push_button(&map);
-        map = new_map;
-        low_count += lows.len();
-        high_count += highs.len();
-    }
-    println!("{}", low_count * high_count);
+    common::init_tracing();
+    let init = info_span!("parse")
+        .in_scope(|| common::model::load_or_exit(|| parse_lines(&read_lines("input.txt"))));
+    info_span!("part1").in_scope(|| {
+        let mut map = init.clone();
+        let mut low_count = 0;
+        let mut high_count = 0;
+        for _ in 0..1_000 {
+            let (new_map, lows, highs) = push_button(&map);
+            map = new_map;
+            low_count += lows.len();
+            high_count += highs.len();
+        }
+        println!("{}", low_count * high_count);
+        PULSES.report();
+    });
+    let _span = info_span!("part2").entered();
     let mut map = init.clone();
     get_insight(&map); // Get mermaid graph for visualization
     let rx = map.get("rx").unwrap();
@@ -203,4 +259,32 @@ fn main() {
             .for_each(|s| drop(periods.entry(s.clone()).and_modify(|v| *v = cnt)));
     }
     println!("{}", periods.values().product::<usize>());
+    PULSES.report();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::{dump, from_str, Format};
+
+    #[test]
+    fn model_round_trip() {
+        let lines = [
+            "broadcaster -> a, b, c",
+            "%a -> b",
+            "%b -> c",
+            "%c -> inv",
+            "&inv -> a",
+        ];
+        let model = parse_lines(&lines.map(|l| l.to_string()));
+        for format in [Format::Json, Format::Ron] {
+            let text = dump(&model, format).unwrap();
+            assert_eq!(
+                from_str::<BTreeMap<String, Node>>(&text, format),
+                Ok(model.clone()),
+                "{}",
+                text
+            );
+        }
+    }
 }
@@ -1,6 +1,9 @@
+use common::Counter;
+use grid_cell::GridCell;
 use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Read;
+use tracing::{debug, info_span};
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -9,13 +12,15 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
 enum Grid {
+    #[cell('.')]
     Plot,
+    #[cell('#')]
     Rock,
 }
 
-fn parse_input(lines: &Vec<String>) -> (Vec<Vec<Grid>>, (usize, usize)) {
+fn parse_input(lines: &[String]) -> (Vec<Vec<Grid>>, (usize, usize)) {
     let (mut row, mut col) = (0, 0);
     let map = lines
         .iter()
@@ -24,13 +29,12 @@ fn parse_input(lines: &Vec<String>) -> (Vec<Vec<Grid>>, (usize, usize)) {
             line.chars()
                 .enumerate()
                 .map(|(c, ch)| match ch {
-                    '.' => Grid::Plot,
-                    '#' => Grid::Rock,
+                    // The starting point is a plot that the cell mapping doesn't know about
                     'S' => {
                         (row, col) = (r, c);
                         Grid::Plot
                     }
-                    _ => panic!("Invalid character: {}", ch),
+                    _ => Grid::try_from(ch).unwrap(),
                 })
                 .collect()
         })
@@ -38,12 +42,15 @@ fn parse_input(lines: &Vec<String>) -> (Vec<Vec<Grid>>, (usize, usize)) {
     (map, (row, col))
 }
 
-fn move_steps(map: &Vec<Vec<Grid>>, (row, col): (usize, usize), max_steps: usize) -> usize {
+static EXPANSIONS: Counter = Counter::new("bfs_expansions");
+
+fn move_steps(map: &[Vec<Grid>], (row, col): (usize, usize), max_steps: usize) -> usize {
     let mut queue = VecDeque::from([(row, col)]);
     let mut visited = vec![vec![None; map[0].len()]; map.len()];
     visited[row][col] = Some(0usize);
     while !queue.is_empty() {
         let (row, col) = queue.pop_front().unwrap();
+        EXPANSIONS.incr();
         let step = visited[row][col].unwrap();
         if step < max_steps {
             let mut next = Vec::new();
@@ -71,21 +78,17 @@ fn move_steps(map: &Vec<Vec<Grid>>, (row, col): (usize, usize), max_steps: usize
         .iter()
         .flatten()
         .flatten()
-        .filter(|&x| (max_steps - *x) % 2 == 0)
+        .filter(|&x| (max_steps - *x).is_multiple_of(2))
         .count()
 }
 
-fn move_steps_infinite(
-    map: &Vec<Vec<Grid>>,
-    (row, col): (usize, usize),
-    max_steps: usize,
-) -> usize {
+fn move_steps_infinite(map: &[Vec<Grid>], (row, col): (usize, usize), max_steps: usize) -> usize {
     assert!(map.len() == map[0].len()); // Square map
     let n = map.len();
     assert!(n % 2 == 1); // The chunks will be in alternate even and odd steps
     assert!(row == n / 2 && col == n / 2); // Starting point is in the middle
-    assert!((max_steps - (n / 2)) % n == 0); // max_steps is multiple of n after first chunk
-    assert!(((max_steps - (n / 2)) / n) % 2 == 0); // max_steps is multiple of n after first chunk
+    assert!((max_steps - (n / 2)).is_multiple_of(n)); // max_steps is multiple of n after first chunk
+    assert!(((max_steps - (n / 2)) / n).is_multiple_of(2)); // max_steps is multiple of n after first chunk
     let chunks = (max_steps - (n / 2)) / n;
     // Make the map 5 times bigger in each direction for our interpolation
     let extended = map
@@ -101,19 +104,128 @@ fn move_steps_infinite(
     let c = k1;
     let b = (4 * k2 - 3 * k1 - k3) / 2;
     let a = k2 - k1 - b;
-    println!("{a}x^2 + {b}x + {c}");
+    debug!("{a}x^2 + {b}x + {c}");
     a * chunks.pow(2) + c + b * chunks
 }
 
+/// Same as `move_steps_infinite`, but counts the reached plots tile by tile. The tiles
+/// inside the diamond are filled and alternate parity, the ones at its tips are entered
+/// from the middle of a side, and the ones along its edges from a corner.
+fn move_steps_infinite_tiles(
+    map: &[Vec<Grid>],
+    (row, col): (usize, usize),
+    max_steps: usize,
+) -> usize {
+    let n = map.len();
+    assert!(map.len() == map[0].len() && n % 2 == 1);
+    assert!(row == n / 2 && col == n / 2);
+    assert!((max_steps - (n / 2)).is_multiple_of(n));
+    // Filled tiles from the starting one to the tips, excluding both
+    let width = max_steps / n - 1;
+    let same = (width / 2 * 2 + 1).pow(2);
+    let flipped = (width.div_ceil(2) * 2).pow(2);
+    let same_points = move_steps(map, (row, col), 2 * n + max_steps % 2);
+    let flipped_points = move_steps(map, (row, col), 2 * n + 1 - max_steps % 2);
+    let tips = [(n - 1, col), (row, 0), (0, col), (row, n - 1)]
+        .into_iter()
+        .map(|start| move_steps(map, start, n - 1))
+        .sum::<usize>();
+    let corners = [(n - 1, 0), (n - 1, n - 1), (0, 0), (0, n - 1)];
+    let small = corners
+        .into_iter()
+        .map(|start| move_steps(map, start, n / 2 - 1))
+        .sum::<usize>();
+    let large = corners
+        .into_iter()
+        .map(|start| move_steps(map, start, 3 * n / 2 - 1))
+        .sum::<usize>();
+    same * same_points + flipped * flipped_points + tips + (width + 1) * small + width * large
+}
+
 fn main() {
-    let lines = read_lines("input.txt");
-    let (map, (row, col)) = parse_input(&lines);
-    let result = move_steps(&map, (row, col), 64);
-    println!("{}", result);
+    common::init_tracing();
+    let (map, (row, col)) = info_span!("parse").in_scope(|| parse_input(&read_lines("input.txt")));
+    info_span!("part1").in_scope(|| {
+        let result = move_steps(&map, (row, col), 64);
+        println!("{}", result);
+        EXPANSIONS.report();
+    });
+    let _span = info_span!("part2").entered();
     // Infinite map is easy as from starting point to the neighboring chunks
     // the shortest path is in straight line. Therefore the number of chunks
     // it can reach is a quadratic function of the number of steps. And the
     // number of possible positions are also a quadratic function of the number
-    let result = move_steps_infinite(&map, (row, col), 26501365);
+    let result = common::strategy::choose(&[
+        ("extrapolate", &|| {
+            move_steps_infinite(&map, (row, col), 26501365)
+        }),
+        ("tiles", &|| {
+            move_steps_infinite_tiles(&map, (row, col), 26501365)
+        }),
+    ]);
     println!("{}", result);
+    EXPANSIONS.report();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An odd square map with the start in the middle and its row, column and border clear,
+    /// like the puzzle input, and rocks scattered anywhere else
+    fn random_map(n: usize, next: &mut impl FnMut(usize) -> usize) -> Vec<Vec<Grid>> {
+        (0..n)
+            .map(|r| {
+                (0..n)
+                    .map(|c| {
+                        let clear = r == 0 || c == 0 || r == n - 1 || c == n - 1;
+                        match clear || r == n / 2 || c == n / 2 || next(8) != 0 {
+                            true => Grid::Plot,
+                            false => Grid::Rock,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Walk the map repeated far enough in every direction that the walk never leaves it
+    fn brute_force(map: &[Vec<Grid>], max_steps: usize) -> usize {
+        let n = map.len();
+        let repeats = 2 * (max_steps / n + 1) + 1;
+        let tiled = map
+            .iter()
+            .map(|row| row.repeat(repeats))
+            .cycle()
+            .take(repeats * n)
+            .collect::<Vec<_>>();
+        let centre = repeats / 2 * n + n / 2;
+        move_steps(&tiled, (centre, centre), max_steps)
+    }
+
+    #[test]
+    fn strategies_match_brute_force() {
+        // A fixed linear congruential generator, so failures can be replayed
+        let mut seed = 0x2023_u64;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        for n in [5, 7, 11] {
+            for _ in 0..10 {
+                let map = random_map(n, &mut next);
+                let start = (n / 2, n / 2);
+                for chunks in 1..=4 {
+                    let max_steps = n / 2 + chunks * n;
+                    let expected = brute_force(&map, max_steps);
+                    assert_eq!(move_steps_infinite_tiles(&map, start, max_steps), expected);
+                    if chunks % 2 == 0 {
+                        assert_eq!(move_steps_infinite(&map, start, max_steps), expected);
+                    }
+                }
+            }
+        }
+    }
 }
@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+mod render;
+mod schematic;
+
+use schematic::{Aggregate, Pos, Schematic, SymbolFilter};
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -12,122 +16,224 @@ fn read_lines(file_name: &str) -> Vec<String> {
 fn locate_symbols(line: &str) -> Vec<(usize, char)> {
     line.to_string()
         .char_indices()
-        .filter(|&(_, c)| c != '.' && !c.is_digit(10))
+        .filter(|&(_, c)| c != '.' && !c.is_ascii_digit())
         .collect::<Vec<(usize, char)>>()
 }
 
+/// Span and value of the number with a digit at `idx`, `None` if there is no digit there or
+/// the number doesn't fit in a `u64`
 fn try_part_number(s: &str, idx: usize) -> Option<(usize, usize, u64)> {
-    let mut lb = idx;
-    let mut rb = idx + 1;
-    if s[lb..rb].parse::<u64>().is_err() {
+    let bytes = s.as_bytes();
+    if !bytes.get(idx)?.is_ascii_digit() {
         return None;
     }
-    while s[lb - 1..rb].parse::<u64>().is_ok() {
+    let mut lb = idx;
+    let mut rb = idx + 1;
+    while lb > 0 && bytes[lb - 1].is_ascii_digit() {
         lb -= 1;
-        if lb == 0 {
-            break;
-        }
     }
-    while s[lb..rb + 1].parse::<u64>().is_ok() {
+    while rb < bytes.len() && bytes[rb].is_ascii_digit() {
         rb += 1;
-        if rb == s.len() {
-            break;
-        }
     }
-    Some((lb, rb, s[lb..rb].parse::<u64>().unwrap()))
+    Some((lb, rb, s[lb..rb].parse::<u64>().ok()?))
 }
 
-fn find_part_numbers(lines: &Vec<String>) -> Vec<u64> {
-    let mut map = HashMap::new();
-    let mut part_numbers = Vec::new();
-    for (i, line) in lines.iter().enumerate() {
-        let symbol_indices = locate_symbols(&line);
-        for (j, _) in symbol_indices {
-            for di in -1..2 {
-                for dj in -1..2 {
-                    if di == 0 && dj == 0 {
-                        continue;
-                    }
-                    let ni = i as i32 + di;
-                    let nj = j as i32 + dj;
-                    if ni < 0 || nj < 0 {
-                        continue;
-                    }
-                    let ni = ni as usize;
-                    let nj = nj as usize;
-                    if ni >= lines.len() || nj >= lines[ni].len() {
-                        continue;
-                    }
-                    if let Some((lb, rb, number)) =
-                        try_part_number(&lines[ni as usize], nj as usize)
-                    {
-                        match map.get(&(ni, lb, rb)) {
-                            Some(_) => continue,
-                            None => {
-                                map.insert((ni, lb, rb), number);
-                                part_numbers.push(number);
-                            }
-                        }
-                    }
-                }
-            }
+/// A `row col char` edit
+fn parse_edit(line: &str) -> Result<(Pos, char), String> {
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+    match parts[..] {
+        [row, col, ch] if ch.chars().count() == 1 => match (row.parse(), col.parse()) {
+            (Ok(row), Ok(col)) => Ok(((row, col), ch.chars().next().unwrap())),
+            _ => Err(format!("Expected `row col char`: {}", line)),
+        },
+        _ => Err(format!("Expected `row col char`: {}", line)),
+    }
+}
+
+/// `--query <symbols>:<neighbours>:<sum|product>`, e.g. `*:2:product` for the gear ratios,
+/// where `any` matches any symbols or number of neighbours
+fn parse_query(query: &str) -> (SymbolFilter, Aggregate) {
+    let parts = query.split(':').collect::<Vec<_>>();
+    assert!(parts.len() == 3, "Invalid query: {}", query);
+    let filter = SymbolFilter {
+        chars: match parts[0] {
+            "any" => None,
+            chars => Some(chars.chars().collect()),
+        },
+        neighbours: match parts[1] {
+            "any" => None,
+            k => Some(k.parse().unwrap()),
+        },
+    };
+    let aggregate = match parts[2] {
+        "sum" => Aggregate::Sum,
+        "product" => Aggregate::Product,
+        other => panic!("Unknown aggregate: {}", other),
+    };
+    (filter, aggregate)
+}
+
+/// The sum of `values`, `None` if it doesn't fit in a `u128`
+fn total(values: &[u128]) -> Option<u128> {
+    values
+        .iter()
+        .try_fold(0, |sum: u128, &v| sum.checked_add(v))
+}
+
+/// Print `value`, or exit with an error when it overflowed
+fn print_or_exit(value: Option<u128>, what: &str) {
+    match value {
+        Some(value) => println!("{}", value),
+        None => {
+            eprintln!("{} overflows u128", what);
+            std::process::exit(1);
         }
     }
-    part_numbers
 }
 
-fn find_gears(lines: &Vec<String>) -> Vec<u64> {
-    let mut gear_ratios = Vec::new();
-    for (i, line) in lines.iter().enumerate() {
-        let symbol_indices = locate_symbols(&line);
-        for (j, ch) in symbol_indices {
-            let mut map = HashMap::new();
-            let mut part_numbers = Vec::new();
-            if ch != '*' {
+/// Both sums as printed by `--edit`, with `-` for a gear ratio sum that overflows
+fn sums(schematic: &Schematic) -> String {
+    let gears = match schematic.gear_ratio_sum() {
+        Some(sum) => sum.to_string(),
+        None => "-".to_string(),
+    };
+    format!("{} {}", schematic.part_number_sum(), gears)
+}
+
+fn main() {
+    common::init_tracing();
+    let lines = read_lines("input.txt");
+    let schematic = info_span!("parse").in_scope(|| Schematic::parse(&lines));
+    if let Some(format) = common::flag_value("--render") {
+        let format = match format.as_str() {
+            "ansi" => render::Format::Ansi,
+            "html" => render::Format::Html,
+            other => panic!("Unknown format: {}", other),
+        };
+        print!("{}", render::render(&schematic, format));
+        return;
+    }
+    // Read `row col char` edits from stdin, printing both sums after every one of them
+    if common::has_flag("--edit") {
+        let mut schematic = schematic;
+        println!("{}", sums(&schematic));
+        for line in std::io::stdin().lines() {
+            let line = line.unwrap();
+            if let Err(error) = parse_edit(&line).and_then(|(pos, ch)| schematic.edit(pos, ch)) {
+                eprintln!("{}", error);
                 continue;
             }
-            for di in -1..2 {
-                for dj in -1..2 {
-                    if di == 0 && dj == 0 {
-                        continue;
-                    }
-                    let ni = i as i32 + di;
-                    let nj = j as i32 + dj;
-                    if ni < 0 || nj < 0 {
-                        continue;
-                    }
-                    let ni = ni as usize;
-                    let nj = nj as usize;
-                    if ni >= lines.len() || nj >= lines[ni].len() {
-                        continue;
-                    }
-                    if let Some((lb, rb, number)) =
-                        try_part_number(&lines[ni as usize], nj as usize)
-                    {
-                        match map.get(&(ni, lb, rb)) {
-                            Some(_) => continue,
-                            None => {
-                                map.insert((ni, lb, rb), number);
-                                part_numbers.push(number);
-                            }
-                        }
-                    }
-                }
-            }
-            if part_numbers.len() == 2 {
-                gear_ratios.push(part_numbers.iter().product());
-            }
+            println!("{}", sums(&schematic));
         }
+        return;
     }
-    gear_ratios
+    if let Some(query) = common::flag_value("--query") {
+        let (filter, aggregate) = parse_query(&query);
+        let values = schematic.aggregate(&filter, aggregate);
+        print_or_exit(values.as_deref().and_then(total), "The query");
+        return;
+    }
+    info_span!("part1").in_scope(|| {
+        let part_numbers = schematic.numbers_near(&SymbolFilter::default());
+        let sum: u128 = part_numbers.iter().map(|n| u128::from(n.value)).sum();
+        println!("{}", sum);
+    });
+    info_span!("part2").in_scope(|| {
+        let gears = SymbolFilter {
+            chars: Some(vec!['*']),
+            neighbours: Some(2),
+        };
+        let gear_ratios = schematic.aggregate(&gears, Aggregate::Product);
+        print_or_exit(
+            gear_ratios.as_deref().and_then(total),
+            "The sum of the gear ratios",
+        );
+    });
 }
 
-fn main() {
-    let lines = read_lines("input.txt");
-    let part_numbers = find_part_numbers(&lines);
-    let sum: u64 = part_numbers.iter().sum();
-    println!("{}", sum);
-    let gear_ratios = find_gears(&lines);
-    let product: u64 = gear_ratios.iter().sum();
-    println!("{}", product);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_numbers() {
+        assert_eq!(try_part_number("..35..633.", 3), Some((2, 4, 35)));
+        assert_eq!(try_part_number("..35..633.", 2), Some((2, 4, 35)));
+        assert_eq!(try_part_number("..35..633.", 4), None);
+        assert_eq!(try_part_number("..35..633.", 10), None);
+        assert_eq!(try_part_number("007", 0), Some((0, 3, 7)));
+        assert_eq!(
+            try_part_number("18446744073709551615", 5),
+            Some((0, 20, u64::MAX))
+        );
+        assert_eq!(try_part_number("18446744073709551616*", 5), None);
+    }
+
+    #[test]
+    fn edits() {
+        assert_eq!(parse_edit("1 3 #"), Ok(((1, 3), '#')));
+        assert_eq!(parse_edit(" 0  9 7 "), Ok(((0, 9), '7')));
+        for line in ["1 3", "1 3 ##", "-1 3 #", "a 3 #", "1 3 # 4"] {
+            assert!(parse_edit(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn queries() {
+        let (filter, aggregate) = parse_query("*:2:product");
+        assert_eq!(filter.chars, Some(vec!['*']));
+        assert_eq!(filter.neighbours, Some(2));
+        assert_eq!(aggregate, Aggregate::Product);
+        let (filter, aggregate) = parse_query("any:any:sum");
+        assert_eq!(filter, SymbolFilter::default());
+        assert_eq!(aggregate, Aggregate::Sum);
+        let (filter, _) = parse_query("#$:1:sum");
+        assert_eq!(filter.chars, Some(vec!['#', '$']));
+    }
+
+    #[test]
+    fn query_example() {
+        let lines = [
+            "467..114..",
+            "...*......",
+            "..35..633.",
+            "......#...",
+            "617*......",
+            ".....+.58.",
+            "..592.....",
+            "......755.",
+            "...$.*....",
+            ".664.598..",
+        ]
+        .map(|l| l.to_string());
+        let schematic = Schematic::parse(&lines);
+        let query = |q: &str| {
+            let (filter, aggregate) = parse_query(q);
+            schematic
+                .aggregate(&filter, aggregate)
+                .as_deref()
+                .and_then(total)
+        };
+        assert_eq!(query("*:2:product"), Some(467835));
+        assert_eq!(query("any:1:sum"), Some(633 + 617 + 592 + 664));
+        assert_eq!(query("*:any:sum"), Some(467 + 35 + 617 + 755 + 598));
+        assert_eq!(query("+:2:sum"), Some(0));
+        assert_eq!(sums(&schematic), "4361 467835");
+    }
+
+    #[test]
+    fn sums_past_u128() {
+        let gear = "18446744073709551615*18446744073709551615";
+        let schematic = Schematic::parse(&[gear, "", gear].map(|l| l.to_string()));
+        let max = u128::from(u64::MAX);
+        assert_eq!(sums(&schematic), format!("{} -", 4 * max));
+        assert_eq!(total(&[max * max, max * max]), None);
+        assert_eq!(total(&[u128::MAX - 1, 1]), Some(u128::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown aggregate: mean")]
+    fn unknown_aggregate() {
+        parse_query("*:2:mean");
+    }
 }
@@ -0,0 +1,145 @@
+use crate::schematic::{Schematic, SymbolFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Terminal colours
+    Ansi,
+    /// A standalone page
+    Html,
+}
+
+/// What a cell of the schematic turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Empty,
+    /// Digit of a number next to a symbol
+    Part,
+    /// Digit of a number next to no symbol
+    Lone,
+    /// `*` next to exactly two numbers
+    Gear,
+    Symbol,
+}
+
+impl Kind {
+    fn ansi(self) -> &'static str {
+        match self {
+            Kind::Empty => "\x1b[2m",
+            Kind::Part => "\x1b[32m",
+            Kind::Lone => "\x1b[31m",
+            Kind::Gear => "\x1b[1;33m",
+            Kind::Symbol => "\x1b[36m",
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            Kind::Empty => "empty",
+            Kind::Part => "part",
+            Kind::Lone => "lone",
+            Kind::Gear => "gear",
+            Kind::Symbol => "symbol",
+        }
+    }
+}
+
+const STYLE: &str = ".empty { color: #bbb; } .part { color: #080; } .lone { color: #c00; } \
+.gear { color: #b80; font-weight: bold; } .symbol { color: #08b; }";
+
+/// The kind of every cell, by row and byte offset in the row like the schematic's positions
+fn classify(schematic: &Schematic) -> Vec<Vec<Kind>> {
+    let mut kinds = schematic
+        .lines()
+        .iter()
+        .map(|line| vec![Kind::Empty; line.len()])
+        .collect::<Vec<_>>();
+    for number in schematic.numbers() {
+        kinds[number.row][number.start..number.end].fill(Kind::Lone);
+    }
+    for (row, col) in schematic.symbols(&SymbolFilter::default()) {
+        kinds[row][col] = Kind::Symbol;
+        for number in schematic.neighbours((row, col)) {
+            kinds[number.row][number.start..number.end].fill(Kind::Part);
+        }
+    }
+    let gears = SymbolFilter {
+        chars: Some(vec!['*']),
+        neighbours: Some(2),
+    };
+    for (row, col) in schematic.symbols(&gears) {
+        kinds[row][col] = Kind::Gear;
+    }
+    kinds
+}
+
+/// The schematic with part numbers, numbers not next to any symbol, gears and other symbols
+/// each in their own colour
+pub fn render(schematic: &Schematic, format: Format) -> String {
+    let kinds = classify(schematic);
+    let rows = schematic.lines().iter().zip(kinds).map(|(line, kinds)| {
+        line.char_indices()
+            .map(|(i, ch)| (ch, kinds[i]))
+            .map(|(ch, kind)| match format {
+                Format::Ansi => format!("{}{}\x1b[0m", kind.ansi(), ch),
+                Format::Html => {
+                    let ch = match ch {
+                        '&' => "&amp;".to_string(),
+                        '<' => "&lt;".to_string(),
+                        '>' => "&gt;".to_string(),
+                        ch => ch.to_string(),
+                    };
+                    format!("<span class=\"{}\">{}</span>", kind.class(), ch)
+                }
+            })
+            .collect::<String>()
+    });
+    match format {
+        Format::Ansi => rows.map(|row| row + "\n").collect(),
+        Format::Html => format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><style>{}</style></head><body><pre>\n{}</pre></body></html>\n",
+            STYLE,
+            rows.map(|row| row + "\n").collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schematic(lines: &[&str]) -> Schematic {
+        Schematic::parse(&lines.iter().map(|l| l.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn kinds() {
+        let schematic = schematic(&["467..114..", "...*......", "..35.#...."]);
+        let kinds = classify(&schematic);
+        assert_eq!(kinds[0][..3], [Kind::Part; 3]);
+        assert_eq!(kinds[0][5..8], [Kind::Lone; 3]);
+        assert_eq!(kinds[1][3], Kind::Gear);
+        assert_eq!(kinds[2][5], Kind::Symbol);
+        assert_eq!(kinds[2][0], Kind::Empty);
+    }
+
+    #[test]
+    fn non_ascii() {
+        // `é` takes two bytes, so the cells after it must still be looked up by byte offset
+        let schematic = schematic(&["é.12*.7"]);
+        let colours = ["36", "2", "32", "32", "36", "2", "31"];
+        let expected = "é.12*.7"
+            .chars()
+            .zip(colours)
+            .map(|(ch, colour)| format!("\x1b[{}m{}\x1b[0m", colour, ch))
+            .collect::<String>();
+        assert_eq!(render(&schematic, Format::Ansi), expected + "\n");
+    }
+
+    #[test]
+    fn html() {
+        let page = render(&schematic(&["1<."]), Format::Html);
+        assert!(page.contains(
+            "<pre>\n<span class=\"part\">1</span><span class=\"symbol\">&lt;</span><span class=\"empty\">.</span>\n</pre>"
+        ));
+    }
+}
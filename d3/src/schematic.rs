@@ -0,0 +1,556 @@
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::warn;
+
+/// Row and column of a cell
+pub type Pos = (usize, usize);
+
+/// A number of the schematic, in columns `start..end` of its row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Number {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub value: u64,
+}
+
+impl Number {
+    pub fn pos(&self) -> Pos {
+        (self.row, self.start)
+    }
+}
+
+/// How to put the numbers around a symbol together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Product,
+}
+
+impl Aggregate {
+    /// `None` if the result doesn't fit in a `u128`
+    pub fn apply(self, values: impl Iterator<Item = u64>) -> Option<u128> {
+        let mut values = values.map(u128::from);
+        match self {
+            Aggregate::Sum => values.try_fold(0, u128::checked_add),
+            Aggregate::Product => values.try_fold(1, u128::checked_mul),
+        }
+    }
+}
+
+/// Which symbols a query is about. `None` matches anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolFilter {
+    pub chars: Option<Vec<char>>,
+    /// Exact number of numbers around the symbol
+    pub neighbours: Option<usize>,
+}
+
+/// Numbers and symbols of a schematic, with which numbers are around which symbols.
+///
+/// The sum of the part numbers and the sum of the gear ratios are kept up to date as cells
+/// are edited.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schematic {
+    lines: Vec<String>,
+    /// Numbers by position of their first digit
+    numbers: BTreeMap<Pos, Number>,
+    symbols: BTreeMap<Pos, char>,
+    /// Numbers around every symbol, by position of their first digit
+    around_symbol: BTreeMap<Pos, BTreeSet<Pos>>,
+    /// Symbols around every number
+    around_number: BTreeMap<Pos, BTreeSet<Pos>>,
+    /// Fewer than 2^64 numbers below 2^64 each, so this can't overflow
+    part_number_sum: u128,
+    /// Every gear ratio fits in a `u128` but their sum may not, so it's kept modulo 2^128
+    /// along with how many times it wrapped
+    gear_ratio_sum: u128,
+    gear_ratio_carry: u64,
+}
+
+impl Schematic {
+    pub fn parse(lines: &[String]) -> Schematic {
+        let mut schematic = Schematic {
+            lines: lines.to_vec(),
+            ..Default::default()
+        };
+        for (row, line) in lines.iter().enumerate() {
+            let mut col = 0;
+            while col < line.len() {
+                match crate::try_part_number(line, col) {
+                    Some((start, end, value)) => {
+                        let number = Number {
+                            row,
+                            start,
+                            end,
+                            value,
+                        };
+                        schematic.numbers.insert(number.pos(), number);
+                        schematic
+                            .around_number
+                            .insert(number.pos(), BTreeSet::new());
+                        col = end;
+                    }
+                    None if line.as_bytes()[col].is_ascii_digit() => {
+                        let len = line[col..].bytes().take_while(u8::is_ascii_digit).count();
+                        warn!(
+                            row,
+                            col,
+                            "Number too large, it is left out: {}",
+                            &line[col..col + len]
+                        );
+                        col += len;
+                    }
+                    None => col += 1,
+                }
+            }
+            for (col, ch) in crate::locate_symbols(line) {
+                schematic.symbols.insert((row, col), ch);
+                schematic.around_symbol.insert((row, col), BTreeSet::new());
+            }
+        }
+        let symbols = schematic.symbols.keys().copied().collect::<Vec<_>>();
+        for &pos in symbols.iter() {
+            schematic.link(pos);
+        }
+        let numbers = schematic.numbers.keys().copied().collect::<Vec<_>>();
+        schematic.count(&numbers, &symbols, true);
+        schematic
+    }
+
+    /// The schematic as currently edited
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// All the numbers, in reading order
+    pub fn numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers.values()
+    }
+
+    pub fn part_number_sum(&self) -> u128 {
+        self.part_number_sum
+    }
+
+    /// `None` if the sum doesn't fit in a `u128`
+    pub fn gear_ratio_sum(&self) -> Option<u128> {
+        (self.gear_ratio_carry == 0).then_some(self.gear_ratio_sum)
+    }
+
+    /// Add (or take back) what the numbers and symbols at these positions bring to the sums
+    fn count(&mut self, numbers: &[Pos], symbols: &[Pos], add: bool) {
+        let parts = numbers
+            .iter()
+            .filter(|pos| !self.around_number[pos].is_empty())
+            .map(|pos| u128::from(self.numbers[pos].value))
+            .sum::<u128>();
+        let gears = symbols
+            .iter()
+            .filter(|pos| self.symbols[pos] == '*' && self.around_symbol[pos].len() == 2)
+            .map(|&pos| {
+                Aggregate::Product
+                    .apply(self.neighbours(pos).map(|n| n.value))
+                    .expect("Two u64 multiply within a u128")
+            })
+            .collect::<Vec<_>>();
+        for gear in gears {
+            let (sum, wrapped) = match add {
+                true => self.gear_ratio_sum.overflowing_add(gear),
+                false => self.gear_ratio_sum.overflowing_sub(gear),
+            };
+            self.gear_ratio_sum = sum;
+            match add {
+                true => self.gear_ratio_carry += wrapped as u64,
+                false => self.gear_ratio_carry -= wrapped as u64,
+            }
+        }
+        match add {
+            true => self.part_number_sum += parts,
+            false => self.part_number_sum -= parts,
+        }
+    }
+
+    /// Numbers and symbols whose contribution to the sums an edit of `pos` can change: the
+    /// ones in the 3x3 square around it, and the symbols around those numbers
+    fn affected(&self, (row, col): Pos) -> (Vec<Pos>, Vec<Pos>) {
+        let square = (row.saturating_sub(1)..=row + 1)
+            .flat_map(|r| (col.saturating_sub(1)..=col + 1).map(move |c| (r, c)))
+            .collect::<Vec<_>>();
+        let numbers = square
+            .iter()
+            .filter_map(|&pos| self.number_at(pos).map(|n| n.pos()))
+            .collect::<BTreeSet<_>>();
+        let symbols = square
+            .iter()
+            .filter(|pos| self.symbols.contains_key(pos))
+            .copied()
+            .chain(
+                numbers
+                    .iter()
+                    .flat_map(|n| self.around_number[n].iter().copied()),
+            )
+            .collect::<BTreeSet<_>>();
+        (numbers.into_iter().collect(), symbols.into_iter().collect())
+    }
+
+    fn remove_number(&mut self, pos: Pos) {
+        self.numbers.remove(&pos);
+        for symbol in self.around_number.remove(&pos).unwrap() {
+            self.around_symbol.get_mut(&symbol).unwrap().remove(&pos);
+        }
+    }
+
+    fn add_number(&mut self, number: Number) {
+        self.numbers.insert(number.pos(), number);
+        self.around_number.insert(number.pos(), BTreeSet::new());
+        for r in number.row.saturating_sub(1)..=number.row + 1 {
+            for c in number.start.saturating_sub(1)..=number.end {
+                if self.symbols.contains_key(&(r, c)) {
+                    self.around_symbol
+                        .get_mut(&(r, c))
+                        .unwrap()
+                        .insert(number.pos());
+                    self.around_number
+                        .get_mut(&number.pos())
+                        .unwrap()
+                        .insert((r, c));
+                }
+            }
+        }
+    }
+
+    fn remove_symbol(&mut self, pos: Pos) {
+        if self.symbols.remove(&pos).is_some() {
+            for number in self.around_symbol.remove(&pos).unwrap() {
+                self.around_number.get_mut(&number).unwrap().remove(&pos);
+            }
+        }
+    }
+
+    /// Write `ch` at `pos` and update the numbers, symbols and sums around it
+    pub fn edit(&mut self, (row, col): Pos, ch: char) -> Result<(), String> {
+        if !ch.is_ascii() {
+            return Err(format!("Only ASCII cells are supported: {}", ch));
+        }
+        let line = self
+            .lines
+            .get(row)
+            .filter(|line| col < line.len())
+            .ok_or_else(|| format!("({}, {}) is out of the schematic", row, col))?;
+        if !line.is_char_boundary(col) || !line.is_char_boundary(col + 1) {
+            return Err(format!("({}, {}) is not an ASCII cell", row, col));
+        }
+        let (numbers, symbols) = self.affected((row, col));
+        self.count(&numbers, &symbols, false);
+        // Numbers next to the cell can be split or merged, so they're all read again
+        let around = col.saturating_sub(1)..=col + 1;
+        for c in around.clone() {
+            if let Some(number) = self.number_at((row, c)).map(|n| n.pos()) {
+                self.remove_number(number);
+            }
+        }
+        self.remove_symbol((row, col));
+        self.lines[row].replace_range(col..col + 1, &ch.to_string());
+        for c in around {
+            if self.number_at((row, c)).is_none() {
+                if let Some((start, end, value)) = crate::try_part_number(&self.lines[row], c) {
+                    self.add_number(Number {
+                        row,
+                        start,
+                        end,
+                        value,
+                    });
+                }
+            }
+        }
+        if ch != '.' && !ch.is_ascii_digit() {
+            self.symbols.insert((row, col), ch);
+            self.around_symbol.insert((row, col), BTreeSet::new());
+            self.link((row, col));
+        }
+        // Whatever was affected before and still exists is counted again along the new ones
+        let (mut after_numbers, mut after_symbols) = self.affected((row, col));
+        after_numbers.extend(numbers.into_iter().filter(|n| self.numbers.contains_key(n)));
+        after_symbols.extend(symbols.into_iter().filter(|s| self.symbols.contains_key(s)));
+        after_numbers.sort();
+        after_numbers.dedup();
+        after_symbols.sort();
+        after_symbols.dedup();
+        self.count(&after_numbers, &after_symbols, true);
+        Ok(())
+    }
+
+    /// The number with a digit at `pos`
+    pub fn number_at(&self, (row, col): Pos) -> Option<&Number> {
+        self.numbers
+            .range((row, 0)..=(row, col))
+            .next_back()
+            .map(|(_, n)| n)
+            .filter(|n| col < n.end)
+    }
+
+    /// Connect the symbol at `pos` with the numbers around it
+    fn link(&mut self, (row, col): Pos) {
+        for r in row.saturating_sub(1)..=row + 1 {
+            for c in col.saturating_sub(1)..=col + 1 {
+                if let Some(number) = self.number_at((r, c)).map(|n| n.pos()) {
+                    self.around_symbol
+                        .get_mut(&(row, col))
+                        .unwrap()
+                        .insert(number);
+                    self.around_number
+                        .get_mut(&number)
+                        .unwrap()
+                        .insert((row, col));
+                }
+            }
+        }
+    }
+
+    /// Numbers around the symbol at `pos`
+    pub fn neighbours(&self, pos: Pos) -> impl Iterator<Item = &Number> {
+        self.around_symbol[&pos].iter().map(|p| &self.numbers[p])
+    }
+
+    /// Symbols matching `filter`, in reading order
+    pub fn symbols(&self, filter: &SymbolFilter) -> Vec<Pos> {
+        self.symbols
+            .iter()
+            .filter(|(_, ch)| filter.chars.as_ref().is_none_or(|c| c.contains(ch)))
+            .filter(|(pos, _)| {
+                filter
+                    .neighbours
+                    .is_none_or(|k| self.around_symbol[pos].len() == k)
+            })
+            .map(|(pos, _)| *pos)
+            .collect()
+    }
+
+    /// Numbers around at least one of the symbols matching `filter`, in reading order
+    pub fn numbers_near(&self, filter: &SymbolFilter) -> Vec<&Number> {
+        self.symbols(filter)
+            .into_iter()
+            .flat_map(|pos| self.around_symbol[&pos].iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|p| &self.numbers[&p])
+            .collect()
+    }
+
+    /// The numbers around every symbol matching `filter`, put together with `aggregate`.
+    /// `None` if one of them doesn't fit in a `u128`.
+    pub fn aggregate(&self, filter: &SymbolFilter, aggregate: Aggregate) -> Option<Vec<u128>> {
+        self.symbols(filter)
+            .into_iter()
+            .map(|pos| aggregate.apply(self.neighbours(pos).map(|n| n.value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [&str; 10] = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ];
+
+    fn example() -> Schematic {
+        Schematic::parse(&EXAMPLE.map(|l| l.to_string()))
+    }
+
+    fn values(numbers: &[&Number]) -> Vec<u64> {
+        numbers.iter().map(|n| n.value).collect()
+    }
+
+    #[test]
+    fn index() {
+        let schematic = example();
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.symbols.len(), 6);
+        for col in 5..8 {
+            assert_eq!(schematic.number_at((0, col)).map(|n| n.value), Some(114));
+        }
+        assert_eq!(schematic.number_at((0, 4)), None);
+        assert_eq!(schematic.number_at((0, 8)), None);
+        let gear = schematic
+            .neighbours((1, 3))
+            .map(|n| n.value)
+            .collect::<Vec<_>>();
+        assert_eq!(gear, [467, 35]);
+        assert_eq!(schematic.around_number[&(2, 2)], BTreeSet::from([(1, 3)]));
+        assert!(schematic.around_number[&(0, 5)].is_empty());
+        assert_eq!(schematic.part_number_sum(), 4361);
+        assert_eq!(schematic.gear_ratio_sum(), Some(467835));
+    }
+
+    #[test]
+    fn symbol_filter() {
+        let schematic = example();
+        let symbols = |chars: Option<&str>, neighbours| {
+            schematic.symbols(&SymbolFilter {
+                chars: chars.map(|c| c.chars().collect()),
+                neighbours,
+            })
+        };
+        assert_eq!(symbols(None, None).len(), 6);
+        assert_eq!(symbols(Some("*"), None), [(1, 3), (4, 3), (8, 5)]);
+        assert_eq!(symbols(Some("*"), Some(2)), [(1, 3), (8, 5)]);
+        assert_eq!(symbols(None, Some(1)), [(3, 6), (4, 3), (5, 5), (8, 3)]);
+        assert_eq!(symbols(Some("#$"), None), [(3, 6), (8, 3)]);
+        assert_eq!(symbols(Some("%"), None), []);
+        assert_eq!(symbols(None, Some(0)), []);
+        let near = schematic.numbers_near(&SymbolFilter {
+            chars: Some(vec!['*']),
+            neighbours: Some(1),
+        });
+        assert_eq!(values(&near), [617]);
+        let parts = schematic.numbers_near(&SymbolFilter::default());
+        assert_eq!(values(&parts), [467, 35, 633, 617, 592, 755, 664, 598]);
+    }
+
+    #[test]
+    fn aggregate() {
+        let schematic = example();
+        let stars = SymbolFilter {
+            chars: Some(vec!['*']),
+            neighbours: None,
+        };
+        assert_eq!(
+            schematic.aggregate(&stars, Aggregate::Product),
+            Some(vec![16345, 617, 451490])
+        );
+        assert_eq!(
+            schematic.aggregate(&stars, Aggregate::Sum),
+            Some(vec![502, 617, 1353])
+        );
+        assert_eq!(Aggregate::Sum.apply(std::iter::empty()), Some(0));
+        assert_eq!(Aggregate::Product.apply(std::iter::empty()), Some(1));
+    }
+
+    #[test]
+    fn aggregate_past_u64() {
+        let max = u128::from(u64::MAX);
+        let apply = |aggregate: Aggregate, n| aggregate.apply(std::iter::repeat_n(u64::MAX, n));
+        assert_eq!(apply(Aggregate::Sum, 3), Some(3 * max));
+        assert_eq!(apply(Aggregate::Product, 2), Some(max * max));
+        assert_eq!(apply(Aggregate::Product, 3), None);
+        let lines = ["18446744073709551615*18446744073709551615*18446744073709551615"];
+        let schematic = Schematic::parse(&lines.map(|l| l.to_string()));
+        let any = SymbolFilter::default();
+        assert_eq!(
+            schematic.aggregate(&any, Aggregate::Product),
+            Some(vec![max * max, max * max])
+        );
+        assert_eq!(schematic.part_number_sum(), 3 * max);
+        let lines = [lines[0], "18446744073709551615"];
+        let schematic = Schematic::parse(&lines.map(|l| l.to_string()));
+        assert_eq!(schematic.aggregate(&any, Aggregate::Product), None);
+        assert_eq!(
+            schematic.aggregate(&any, Aggregate::Sum),
+            Some(vec![3 * max, 2 * max])
+        );
+    }
+
+    #[test]
+    fn gear_ratio_sum_past_u128() {
+        let gear = "18446744073709551615*18446744073709551615";
+        let lines = [gear, "", gear].map(|l| l.to_string());
+        let mut schematic = Schematic::parse(&lines);
+        let max = u128::from(u64::MAX);
+        assert_eq!(schematic.part_number_sum(), 4 * max);
+        assert_eq!(schematic.gear_ratio_sum(), None);
+        schematic.edit((2, 20), '.').unwrap();
+        assert_eq!(schematic.gear_ratio_sum(), Some(max * max));
+        schematic.edit((2, 20), '*').unwrap();
+        assert_eq!(schematic, Schematic::parse(&lines));
+        schematic.edit((0, 20), '#').unwrap();
+        assert_eq!(schematic.gear_ratio_sum(), Some(max * max));
+    }
+
+    /// Cells an edit can write, covering every kind of cell
+    const CELLS: [char; 7] = ['.', '0', '5', '9', '*', '#', '$'];
+
+    /// `lines` with `ch` written at `pos`
+    fn edited(lines: &[String], (row, col): Pos, ch: char) -> Vec<String> {
+        let mut lines = lines.to_vec();
+        lines[row].replace_range(col..col + 1, &ch.to_string());
+        lines
+    }
+
+    #[test]
+    fn every_single_edit_matches_parse() {
+        let lines = EXAMPLE.map(|l| l.to_string());
+        for row in 0..lines.len() {
+            for col in 0..lines[row].len() {
+                for ch in CELLS {
+                    let mut schematic = example();
+                    schematic.edit((row, col), ch).unwrap();
+                    let expected = Schematic::parse(&edited(&lines, (row, col), ch));
+                    assert_eq!(schematic, expected, "{} at ({}, {})", ch, row, col);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn random_edits_match_parse() {
+        // A fixed linear congruential generator, so failures can be replayed
+        let mut seed = 0x2023_u64;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        for _ in 0..100 {
+            let mut lines = EXAMPLE.map(|l| l.to_string()).to_vec();
+            let mut schematic = example();
+            for _ in 0..30 {
+                let pos = (next(lines.len()), next(lines[0].len()));
+                let ch = CELLS[next(CELLS.len())];
+                schematic.edit(pos, ch).unwrap();
+                lines = edited(&lines, pos, ch);
+                assert_eq!(schematic, Schematic::parse(&lines), "{:?}", lines);
+            }
+        }
+    }
+
+    #[test]
+    fn edit_errors() {
+        let mut schematic = example();
+        assert_eq!(
+            schematic.edit((10, 0), '.'),
+            Err("(10, 0) is out of the schematic".to_string())
+        );
+        assert_eq!(
+            schematic.edit((0, 10), '.'),
+            Err("(0, 10) is out of the schematic".to_string())
+        );
+        assert!(schematic.edit((0, 0), 'é').is_err());
+        assert_eq!(schematic, example());
+        let mut schematic = Schematic::parse(&["1é*".to_string()]);
+        assert_eq!(
+            schematic.edit((0, 2), '.'),
+            Err("(0, 2) is not an ASCII cell".to_string())
+        );
+        assert_eq!(schematic.edit((0, 3), '2'), Ok(()));
+        assert_eq!(schematic, Schematic::parse(&["1é2".to_string()]));
+    }
+
+    #[test]
+    fn number_too_large() {
+        let lines = ["1*99999999999999999999", "..7"].map(|l| l.to_string());
+        let schematic = Schematic::parse(&lines);
+        assert_eq!(schematic.numbers.len(), 2);
+        assert_eq!(schematic.part_number_sum(), 8);
+    }
+}
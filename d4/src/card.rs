@@ -0,0 +1,65 @@
+use crate::count::Count;
+use std::collections::{BTreeSet, HashSet};
+
+/// A scratchcard, with its numbers in the order they're printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub id: u64,
+    pub winning: Vec<u64>,
+    pub held: Vec<u64>,
+}
+
+impl Card {
+    /// Number of held numbers that are also winning numbers. A number held twice counts twice.
+    pub fn matches(&self) -> usize {
+        let winning = self.winning.iter().collect::<HashSet<_>>();
+        self.held.iter().filter(|n| winning.contains(n)).count()
+    }
+
+    /// Numbers printed more than once in the winning list, and in the held list
+    pub fn duplicates(&self) -> (Vec<u64>, Vec<u64>) {
+        fn repeated(numbers: &[u64]) -> Vec<u64> {
+            let mut seen = BTreeSet::new();
+            let mut repeated = BTreeSet::new();
+            for &n in numbers {
+                if !seen.insert(n) {
+                    repeated.insert(n);
+                }
+            }
+            repeated.into_iter().collect()
+        }
+        (repeated(&self.winning), repeated(&self.held))
+    }
+}
+
+/// How many points a card is worth for its number of matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    /// One point for the first match, doubled for every other one, as in the puzzle
+    Doubling,
+    /// One point per match
+    Linear,
+    /// One point per card with any match
+    Any,
+}
+
+impl Scoring {
+    /// Read the rule from `--scoring <doubling|linear|any>`
+    pub fn from_args() -> Scoring {
+        match common::flag_value("--scoring").as_deref() {
+            None | Some("doubling") => Scoring::Doubling,
+            Some("linear") => Scoring::Linear,
+            Some("any") => Scoring::Any,
+            Some(scoring) => panic!("Unknown scoring: {}", scoring),
+        }
+    }
+
+    pub fn points(self, matches: usize) -> Count {
+        match (self, matches) {
+            (_, 0) => Count::default(),
+            (Scoring::Doubling, m) => Count::pow2(m - 1),
+            (Scoring::Linear, m) => Count::from(m as u64),
+            (Scoring::Any, _) => Count::from(1),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::AddAssign;
+
+/// Unsigned integer of any size, for copy counts that double with every card
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Count {
+    /// Base 2^32 digits, least significant first, without leading zeros
+    limbs: Vec<u32>,
+}
+
+impl Count {
+    /// 2 to the power `n`
+    pub fn pow2(n: usize) -> Count {
+        let mut limbs = vec![0; n / 32 + 1];
+        limbs[n / 32] = 1 << (n % 32);
+        Count { limbs }
+    }
+}
+
+impl From<u64> for Count {
+    fn from(n: u64) -> Count {
+        let mut limbs = vec![n as u32, (n >> 32) as u32];
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Count { limbs }
+    }
+}
+
+impl AddAssign<&Count> for Count {
+    fn add_assign(&mut self, other: &Count) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + other.limbs.get(i).copied().unwrap_or(0) as u64 + carry;
+            *limb = sum as u32;
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl<'a> Sum<&'a Count> for Count {
+    fn sum<I: Iterator<Item = &'a Count>>(iter: I) -> Count {
+        iter.fold(Count::default(), |mut total, n| {
+            total += n;
+            total
+        })
+    }
+}
+
+impl Ord for Count {
+    fn cmp(&self, other: &Count) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for Count {
+    fn partial_cmp(&self, other: &Count) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off nine decimal digits at a time
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            let mut rem = 0u64;
+            for limb in limbs.iter_mut().rev() {
+                let cur = (rem << 32) | *limb as u64;
+                *limb = (cur / 1_000_000_000) as u32;
+                rem = cur % 1_000_000_000;
+            }
+            chunks.push(rem);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+        match chunks.pop() {
+            None => write!(f, "0"),
+            Some(top) => {
+                write!(f, "{}", top)?;
+                chunks.iter().rev().try_for_each(|c| write!(f, "{:09}", c))
+            }
+        }
+    }
+}
+
+/// Written as a decimal string, as JSON numbers lose precision past 2^53
+impl Serialize for Count {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(n: u128) -> Count {
+        let mut limbs = (0..4).map(|i| (n >> (32 * i)) as u32).collect::<Vec<_>>();
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Count { limbs }
+    }
+
+    /// Values next to every limb boundary that fits in a u128
+    fn boundaries() -> Vec<u128> {
+        let mut values = vec![0, 1, 2, u128::MAX / 2, u128::MAX - 1, u128::MAX];
+        for shift in [32, 64, 96] {
+            let power = 1u128 << shift;
+            values.extend([power - 2, power - 1, power, power + 1, power * 3 - 1]);
+        }
+        values
+    }
+
+    #[test]
+    fn matches_u128() {
+        let values = boundaries();
+        for &a in &values {
+            assert_eq!(count(a).to_string(), a.to_string());
+            if let Ok(a) = u64::try_from(a) {
+                assert_eq!(Count::from(a), count(a as u128));
+            }
+            for &b in &values {
+                assert_eq!(count(a).cmp(&count(b)), a.cmp(&b), "{} <=> {}", a, b);
+                if let Some(sum) = a.checked_add(b) {
+                    let mut total = count(a);
+                    total += &count(b);
+                    assert_eq!(total, count(sum), "{} + {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn powers_of_two() {
+        for n in 0..128 {
+            assert_eq!(Count::pow2(n), count(1 << n));
+        }
+    }
+
+    #[test]
+    fn past_u128() {
+        let mut total = count(u128::MAX);
+        total += &count(1);
+        assert_eq!(total, Count::pow2(128));
+        assert_eq!(total.to_string(), "340282366920938463463374607431768211456");
+        assert!(total > count(u128::MAX));
+        let doubled = [Count::pow2(199), Count::pow2(199)].iter().sum::<Count>();
+        assert_eq!(doubled, Count::pow2(200));
+        assert_eq!(
+            doubled.to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert!(Count::pow2(200) > Count::pow2(199));
+    }
+}
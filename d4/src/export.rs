@@ -0,0 +1,160 @@
+use crate::card::{Card, Scoring};
+use crate::count::Count;
+use crate::Copies;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Number of cards listed among the ones contributing the most copies
+const TOP: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CardStats {
+    pub id: u64,
+    pub matches: usize,
+    pub points: Count,
+    /// Copies won from earlier cards, without the original
+    pub copies_won: Count,
+    pub total_copies: Count,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Contributor {
+    pub id: u64,
+    /// Copies of later cards won by all the copies of this one
+    pub copies: Count,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Analytics {
+    pub cards: Vec<CardStats>,
+    /// Number of cards with each number of matches
+    pub match_counts: BTreeMap<usize, usize>,
+    /// Cards that won the most copies, most first
+    pub top_contributors: Vec<Contributor>,
+}
+
+impl Analytics {
+    pub fn new(cards: &[Card], copies: &[Copies], scoring: Scoring) -> Analytics {
+        let mut match_counts = BTreeMap::new();
+        let mut contributed = vec![Count::default(); cards.len()];
+        let stats = cards
+            .iter()
+            .zip(copies)
+            .map(|(card, copies)| {
+                let matches = card.matches();
+                *match_counts.entry(matches).or_insert(0) += 1;
+                for (i, n) in copies.won.iter() {
+                    contributed[*i] += n;
+                }
+                CardStats {
+                    id: card.id,
+                    matches,
+                    points: scoring.points(matches),
+                    copies_won: copies.won.iter().map(|(_, n)| n).sum(),
+                    total_copies: copies.total.clone(),
+                }
+            })
+            .collect();
+        let mut top_contributors = cards
+            .iter()
+            .zip(contributed)
+            .map(|(card, copies)| Contributor {
+                id: card.id,
+                copies,
+            })
+            .filter(|c| c.copies > Count::default())
+            .collect::<Vec<_>>();
+        // Stable, so ties stay in card order
+        top_contributors.sort_by(|a, b| b.copies.cmp(&a.copies));
+        top_contributors.truncate(TOP);
+        Analytics {
+            cards: stats,
+            match_counts,
+            top_contributors,
+        }
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// The cards, the match count distribution and the top contributors as one long table
+    /// of `table,key,field,value` rows, keyed by card id or number of matches
+    pub fn csv(&self) -> String {
+        let mut result = "table,key,field,value\n".to_string();
+        for c in self.cards.iter() {
+            result += &format!("cards,{},matches,{}\n", c.id, c.matches);
+            result += &format!("cards,{},points,{}\n", c.id, c.points);
+            result += &format!("cards,{},copies_won,{}\n", c.id, c.copies_won);
+            result += &format!("cards,{},total_copies,{}\n", c.id, c.total_copies);
+        }
+        for (matches, cards) in self.match_counts.iter() {
+            result += &format!("match_counts,{},cards,{}\n", matches, cards);
+        }
+        for c in self.top_contributors.iter() {
+            result += &format!("top_contributors,{},copies,{}\n", c.id, c.copies);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{number_of_cards, parse_line};
+
+    const EXAMPLE: [&str; 6] = [
+        "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+        "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+        "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+        "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    ];
+
+    fn example() -> Analytics {
+        let cards = EXAMPLE.iter().map(|l| parse_line(l)).collect::<Vec<_>>();
+        let values = cards.iter().map(|c| c.matches()).collect::<Vec<_>>();
+        Analytics::new(&cards, &number_of_cards(&values), Scoring::Doubling)
+    }
+
+    #[test]
+    fn csv() {
+        let csv = example().csv();
+        let rows = csv.lines().collect::<Vec<_>>();
+        assert_eq!(rows[0], "table,key,field,value");
+        assert!(rows.iter().all(|row| row.split(',').count() == 4));
+        assert_eq!(
+            rows[1..5],
+            [
+                "cards,1,matches,4",
+                "cards,1,points,8",
+                "cards,1,copies_won,0",
+                "cards,1,total_copies,1"
+            ]
+        );
+        assert!(rows.contains(&"cards,5,total_copies,14"));
+        let tail = &rows[rows.len() - 8..];
+        assert_eq!(
+            tail,
+            [
+                "match_counts,0,cards,2",
+                "match_counts,1,cards,1",
+                "match_counts,2,cards,2",
+                "match_counts,4,cards,1",
+                "top_contributors,3,copies,8",
+                "top_contributors,4,copies,8",
+                "top_contributors,1,copies,4",
+                "top_contributors,2,copies,4",
+            ]
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json: serde_json::Value = serde_json::from_str(&example().json()).unwrap();
+        assert_eq!(json["cards"][4]["total_copies"], "14");
+        assert_eq!(json["match_counts"]["0"], 2);
+        assert_eq!(json["top_contributors"][0]["id"], 3);
+    }
+}
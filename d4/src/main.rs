@@ -1,6 +1,12 @@
-use std::collections::HashSet;
+mod card;
+mod count;
+mod export;
+
+use card::{Card, Scoring};
+use count::Count;
 use std::fs::File;
 use std::io::Read;
+use tracing::{info_span, warn};
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -9,58 +15,110 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-fn parse_line(line: &str) -> u64 {
+fn parse_line(line: &str) -> Card {
     let parts: Vec<&str> = line.split(": ").collect();
     assert!(parts.len() == 2);
+    let id = parts[0]
+        .strip_prefix("Card")
+        .and_then(|id| id.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| panic!("Bad card id: {}", parts[0]));
     let parts: Vec<&str> = parts[1].split(" | ").collect();
     assert!(parts.len() == 2);
-    let targets = parts[0]
-        .split(" ")
-        .map(|s| s.parse::<u64>())
-        .filter(|r| r.is_ok())
-        .map(|r| r.unwrap())
-        .collect::<HashSet<u64>>();
-    let numbers = parts[1]
-        .split(" ")
-        .map(|s| s.parse::<u64>())
-        .filter(|r| r.is_ok())
-        .map(|r| r.unwrap())
-        .collect::<Vec<u64>>();
-    let mut result: u64 = 0;
-    for &x in &numbers {
-        match targets.contains(&x) {
-            true => result += 1,
-            false => {}
-        }
+    let numbers = |s: &str| {
+        s.split(" ")
+            .flat_map(|s| s.parse::<u64>())
+            .collect::<Vec<u64>>()
+    };
+    let card = Card {
+        id,
+        winning: numbers(parts[0]),
+        held: numbers(parts[1]),
+    };
+    let (winning, held) = card.duplicates();
+    if !winning.is_empty() || !held.is_empty() {
+        warn!(card = id, ?winning, ?held, "Repeated numbers");
     }
-    result
+    card
 }
 
-fn number_of_cards(values: Vec<u64>) -> Vec<u64> {
-    let mut counts = vec![1; values.len()];
+/// Copies of a card at the end of the cascade
+#[derive(Debug, Clone, Default)]
+struct Copies {
+    /// The original and all the copies won
+    total: Count,
+    /// Copies won from each earlier card, by index of that card
+    won: Vec<(usize, Count)>,
+}
+
+/// Every card wins one copy of each of the next `values[i]` cards per copy of itself
+fn number_of_cards(values: &[usize]) -> Vec<Copies> {
+    let mut counts = vec![
+        Copies {
+            total: Count::from(1),
+            won: Vec::new(),
+        };
+        values.len()
+    ];
     for i in 0..values.len() {
-        let v = values[i];
-        let c = counts[i];
-        for j in 0..v as usize {
-            if (i + j + 1) >= values.len() {
-                break;
-            }
-            counts[i + j + 1] += c;
+        let c = counts[i].total.clone();
+        for j in (i + 1..=i + values[i]).take_while(|&j| j < values.len()) {
+            counts[j].total += &c;
+            counts[j].won.push((i, c.clone()));
         }
     }
     counts
 }
 
+/// Where the copies of every card came from
+fn trace(cards: &[Card], copies: &[Copies]) -> String {
+    cards
+        .iter()
+        .zip(copies)
+        .map(|(card, copies)| {
+            let sources = copies
+                .won
+                .iter()
+                .map(|(i, n)| format!(", {} from card {}", n, cards[*i].id));
+            format!(
+                "Card {}: {} (1 original{})\n",
+                card.id,
+                copies.total,
+                sources.collect::<String>()
+            )
+        })
+        .collect()
+}
+
 fn main() {
-    let lines = read_lines("input.txt");
-    let values = lines.iter().map(|s| parse_line(s)).collect::<Vec<u64>>();
-    println!(
-        "{}",
-        values
+    common::init_tracing();
+    let cards = info_span!("parse").in_scope(|| {
+        let lines = read_lines("input.txt");
+        lines.iter().map(|s| parse_line(s)).collect::<Vec<Card>>()
+    });
+    let values = cards.iter().map(|c| c.matches()).collect::<Vec<usize>>();
+    if common::has_flag("--trace") {
+        print!("{}", trace(&cards, &number_of_cards(&values)));
+        return;
+    }
+    let scoring = Scoring::from_args();
+    if let Some(format) = common::flag_value("--export") {
+        let analytics = export::Analytics::new(&cards, &number_of_cards(&values), scoring);
+        match format.as_str() {
+            "csv" => print!("{}", analytics.csv()),
+            "json" => println!("{}", analytics.json()),
+            _ => panic!("Unknown format: {}", format),
+        }
+        return;
+    }
+    info_span!("part1").in_scope(|| {
+        let points = values
             .iter()
-            .map(|x| if *x > 0 { 1 << (x - 1) } else { 0 })
-            .sum::<u64>()
-    );
-    let numbers = number_of_cards(values);
-    println!("{}", numbers.iter().sum::<u64>());
+            .map(|&x| scoring.points(x))
+            .collect::<Vec<Count>>();
+        println!("{}", points.iter().sum::<Count>())
+    });
+    info_span!("part2").in_scope(|| {
+        let numbers = number_of_cards(&values);
+        println!("{}", numbers.iter().map(|n| &n.total).sum::<Count>());
+    });
 }
@@ -0,0 +1,408 @@
+use crate::input::Input;
+use crate::range_map::RangeMap;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// A map of the almanac, from the numbers of one category to the ones of another
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub map: RangeMap,
+}
+
+/// The maps of an almanac as a graph between categories, in whatever order they're listed
+#[derive(Debug, Clone, Default)]
+pub struct Almanac {
+    pub edges: Vec<Edge>,
+}
+
+impl Almanac {
+    pub fn new(input: &Input) -> Result<Almanac, String> {
+        let mut almanac = Almanac::default();
+        for m in input.maps.iter() {
+            if almanac.edge(&m.from, &m.to).is_some() {
+                return Err(format!("Two {}-to-{} maps", m.from, m.to));
+            }
+            almanac.edges.push(Edge {
+                from: m.from.clone(),
+                to: m.to.clone(),
+                map: RangeMap::new(m.entries.iter().map(|e| (e.dst, e.src, e.len))),
+            });
+        }
+        almanac.validate()?;
+        Ok(almanac)
+    }
+
+    fn edge(&self, from: &str, to: &str) -> Option<&Edge> {
+        self.edges.iter().find(|e| e.from == from && e.to == to)
+    }
+
+    fn outgoing<'a>(&'a self, from: &'a str) -> impl Iterator<Item = &'a Edge> {
+        self.edges.iter().filter(move |e| e.from == from)
+    }
+
+    pub fn categories(&self) -> BTreeSet<&str> {
+        self.edges
+            .iter()
+            .flat_map(|e| [e.from.as_str(), e.to.as_str()])
+            .collect()
+    }
+
+    /// Check that no category leads back to itself and that all the ways from one category to
+    /// another map the numbers the same
+    fn validate(&self) -> Result<(), String> {
+        // Depth-first search, coloring the categories on the current path
+        fn visit<'a>(
+            almanac: &'a Almanac,
+            category: &'a str,
+            open: &mut Vec<&'a str>,
+            done: &mut BTreeSet<&'a str>,
+        ) -> Result<(), String> {
+            if let Some(i) = open.iter().position(|&c| c == category) {
+                return Err(format!(
+                    "Maps go round in a circle: {}-{}",
+                    open[i..].join("-"),
+                    category
+                ));
+            }
+            if done.contains(category) {
+                return Ok(());
+            }
+            open.push(category);
+            for edge in almanac.outgoing(category) {
+                visit(almanac, &edge.to, open, done)?;
+            }
+            open.pop();
+            done.insert(category);
+            Ok(())
+        }
+        let mut done = BTreeSet::new();
+        for category in self.categories() {
+            visit(self, category, &mut Vec::new(), &mut done)?;
+        }
+        for category in self.categories() {
+            self.check_paths(category)?;
+        }
+        Ok(())
+    }
+
+    /// Check that all the ways from `from` map the numbers the same. The maps are composed along
+    /// a tree of the first way found to each category, and then every map outside the tree
+    /// has to take the tree's map for its source to the tree's map for its target, so that
+    /// each map is composed once instead of once per way.
+    fn check_paths<'a>(&'a self, from: &'a str) -> Result<(), String> {
+        // Breadth-first search, keeping the map to each category and where it came from
+        let mut tree: BTreeMap<&'a str, (RangeMap, Option<&'a str>)> = BTreeMap::new();
+        tree.insert(from, (RangeMap::default(), None));
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            for edge in self.outgoing(category) {
+                if !tree.contains_key(edge.to.as_str()) {
+                    let map = tree[category].0.compose(&edge.map);
+                    tree.insert(&edge.to, (map, Some(category)));
+                    queue.push_back(&edge.to);
+                }
+            }
+        }
+        let way = |mut category: &'a str| {
+            let mut way = vec![category];
+            while let Some(previous) = tree[category].1 {
+                way.push(previous);
+                category = previous;
+            }
+            way.reverse();
+            way.join("-")
+        };
+        for edge in self.edges.iter() {
+            let Some((map, _)) = tree.get(edge.from.as_str()) else {
+                continue;
+            };
+            let (target, previous) = &tree[edge.to.as_str()];
+            if *previous != Some(edge.from.as_str()) && map.compose(&edge.map) != *target {
+                return Err(format!(
+                    "{} and {}-{} map numbers differently",
+                    way(&edge.to),
+                    way(&edge.from),
+                    edge.to
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The categories from `from` to `to` following the fewest maps, forwards or backwards
+    pub fn route(&self, from: &str, to: &str) -> Result<Vec<String>, String> {
+        for category in [from, to] {
+            if !self.categories().contains(category) {
+                return Err(format!("Unknown category: {}", category));
+            }
+        }
+        // Breadth-first search, remembering where each category was reached from
+        let mut previous: BTreeMap<&str, &str> = BTreeMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+            for edge in self.edges.iter() {
+                let next = match (edge.from == category, edge.to == category) {
+                    (true, _) => Some(edge.to.as_str()),
+                    (_, true) => Some(edge.from.as_str()),
+                    _ => None,
+                };
+                if let Some(next) = next {
+                    if next != from && !previous.contains_key(next) {
+                        previous.insert(next, category);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        let mut route = vec![to.to_string()];
+        let mut category = to;
+        while category != from {
+            category = previous
+                .get(category)
+                .ok_or_else(|| format!("No maps lead from {} to {}", from, to))?;
+            route.push(category.to_string());
+        }
+        route.reverse();
+        Ok(route)
+    }
+
+    /// The maps taking the numbers of `from` to numbers of `to`, in order, along `route`. Maps
+    /// followed backwards are inverted.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<RangeMap>, String> {
+        self.route(from, to)?
+            .windows(2)
+            .map(|w| match self.edge(&w[0], &w[1]) {
+                Some(edge) => Ok(edge.map.clone()),
+                None => match self.edge(&w[1], &w[0]).unwrap() {
+                    edge if edge.map.is_bijection() => Ok(edge.map.inverse()),
+                    _ => Err(format!(
+                        "The {}-to-{} map can't be followed backwards, as it sends some numbers to the same place",
+                        w[1], w[0]
+                    )),
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Entry, Map};
+
+    type Entries<'a> = &'a [(u64, u64, u64)];
+
+    fn almanac(maps: &[(&str, &str, Entries)]) -> Result<Almanac, String> {
+        let maps = maps
+            .iter()
+            .map(|(from, to, entries)| Map {
+                line: 0,
+                from: from.to_string(),
+                to: to.to_string(),
+                entries: entries
+                    .iter()
+                    .map(|&(dst, src, len)| Entry {
+                        line: 0,
+                        dst,
+                        src,
+                        len,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Almanac::new(&Input {
+            seeds_line: 1,
+            seeds: Vec::new(),
+            maps,
+        })
+    }
+
+    /// Every way along the maps from `from`, as the composed map to each category reached,
+    /// to check the spanning tree against
+    fn every_way(
+        almanac: &Almanac,
+        from: &str,
+        map: RangeMap,
+        found: &mut Vec<(String, RangeMap)>,
+    ) {
+        for edge in almanac.outgoing(from) {
+            let map = map.compose(&edge.map);
+            found.push((edge.to.clone(), map.clone()));
+            every_way(almanac, &edge.to, map, found);
+        }
+    }
+
+    fn borrowed<'a>(
+        maps: &'a [(String, String, Entries<'a>)],
+    ) -> Vec<(&'a str, &'a str, Entries<'a>)> {
+        maps.iter()
+            .map(|(from, to, entries)| (from.as_str(), to.as_str(), *entries))
+            .collect()
+    }
+
+    const SWAP: Entries = &[(0, 2, 2), (2, 0, 2)];
+    const SHIFT: Entries = &[(1, 0, 3), (0, 3, 1)];
+    const COLLAPSE: Entries = &[(0, 2, 2)];
+
+    #[test]
+    fn new() {
+        let almanac = almanac(&[("soil", "water", SWAP), ("seed", "soil", &[])]).unwrap();
+        assert_eq!(
+            almanac.categories().into_iter().collect::<Vec<_>>(),
+            ["seed", "soil", "water"]
+        );
+        assert_eq!(almanac.edges[0].map, RangeMap::new(SWAP.iter().copied()));
+    }
+
+    #[test]
+    fn new_errors() {
+        assert_eq!(
+            almanac(&[("a", "b", &[]), ("a", "b", SWAP)]).unwrap_err(),
+            "Two a-to-b maps"
+        );
+        assert_eq!(
+            almanac(&[("a", "b", &[]), ("b", "c", &[]), ("c", "a", &[])]).unwrap_err(),
+            "Maps go round in a circle: a-b-c-a"
+        );
+        assert_eq!(
+            almanac(&[("a", "b", SWAP), ("b", "c", &[]), ("a", "c", &[])]).unwrap_err(),
+            "a-c and a-b-c map numbers differently"
+        );
+        // The same numbers either way round
+        almanac(&[("a", "b", SWAP), ("b", "c", SWAP), ("a", "c", &[])]).unwrap();
+    }
+
+    #[test]
+    fn many_ways() {
+        // A chain of diamonds has 2^40 ways from end to end, too many to follow one by one
+        let mut maps = Vec::new();
+        for i in 0..40 {
+            let (start, end) = (format!("c{}", i), format!("c{}", i + 1));
+            let (left, right) = (format!("l{}", i), format!("r{}", i));
+            maps.push((start.clone(), left.clone(), SWAP));
+            maps.push((left, end.clone(), SHIFT));
+            maps.push((start, right.clone(), SWAP));
+            maps.push((right, end, SHIFT));
+        }
+        almanac(&borrowed(&maps)).unwrap();
+        maps.last_mut().unwrap().2 = SWAP;
+        let error = almanac(&borrowed(&maps)).unwrap_err();
+        assert!(error.starts_with("c0-l0-c1-l1-c2"), "{}", error);
+        assert!(error.contains("-c39-l39-c40 and c0-l0-c1"), "{}", error);
+        assert!(
+            error.ends_with("-c39-r39-c40 map numbers differently"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn tree_matches_every_way() {
+        // A fixed linear congruential generator, so failures can be replayed
+        let mut seed = 0x2023_u64;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        let names = ["a", "b", "c", "d", "e"];
+        let pool: [Entries; 4] = [&[], SWAP, SHIFT, COLLAPSE];
+        for _ in 0..2000 {
+            // Maps only go to later categories, so there are no circles
+            let mut maps = Vec::new();
+            for i in 0..names.len() {
+                for j in i + 1..names.len() {
+                    if next(2) == 0 {
+                        maps.push((names[i], names[j], pool[next(pool.len())]));
+                    }
+                }
+            }
+            let graph = Almanac {
+                edges: maps
+                    .iter()
+                    .map(|(from, to, entries)| Edge {
+                        from: from.to_string(),
+                        to: to.to_string(),
+                        map: RangeMap::new(entries.iter().copied()),
+                    })
+                    .collect(),
+            };
+            let consistent = names.iter().all(|from| {
+                let mut found = Vec::new();
+                every_way(&graph, from, RangeMap::default(), &mut found);
+                found
+                    .iter()
+                    .all(|(to, map)| found.iter().all(|(other, m)| other != to || m == map))
+            });
+            assert_eq!(almanac(&maps).is_ok(), consistent, "{:?}", maps);
+        }
+    }
+
+    #[test]
+    fn route() {
+        let graph = almanac(&[
+            ("seed", "soil", SWAP),
+            ("soil", "water", SWAP),
+            ("water", "light", &[]),
+            ("seed", "light", &[]),
+            ("fertilizer", "water", SWAP),
+            ("fertilizer", "humidity", COLLAPSE),
+        ])
+        .unwrap();
+        assert_eq!(graph.route("seed", "light").unwrap(), ["seed", "light"]);
+        assert_eq!(
+            graph.route("seed", "water").unwrap(),
+            ["seed", "soil", "water"]
+        );
+        // Backwards along a map
+        assert_eq!(
+            graph.route("light", "fertilizer").unwrap(),
+            ["light", "water", "fertilizer"]
+        );
+        assert_eq!(graph.route("soil", "soil").unwrap(), ["soil"]);
+        assert_eq!(
+            graph.route("seed", "location").unwrap_err(),
+            "Unknown category: location"
+        );
+        let apart = almanac(&[("a", "b", &[]), ("c", "d", &[])]).unwrap();
+        assert_eq!(
+            apart.route("a", "d").unwrap_err(),
+            "No maps lead from a to d"
+        );
+    }
+
+    #[test]
+    fn path() {
+        let graph = almanac(&[
+            ("seed", "soil", SHIFT),
+            ("fertilizer", "soil", SWAP),
+            ("fertilizer", "water", COLLAPSE),
+        ])
+        .unwrap();
+        let path = graph.path("seed", "fertilizer").unwrap();
+        assert_eq!(
+            path,
+            [
+                RangeMap::new(SHIFT.iter().copied()),
+                RangeMap::new(SWAP.iter().copied())
+            ]
+        );
+        for x in 0..8 {
+            let fertilizer = path[1].apply(path[0].apply(x));
+            assert_eq!(
+                graph.edges[1].map.apply(fertilizer),
+                graph.edges[0].map.apply(x)
+            );
+        }
+        assert_eq!(
+            graph.path("water", "fertilizer").unwrap_err(),
+            "The fertilizer-to-water map can't be followed backwards, as it sends some numbers to the same place"
+        );
+        assert_eq!(graph.path("fertilizer", "water").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::range_map::RangeMap;
+use serde::Serialize;
+
+/// A range of numbers at one stage of the almanac
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Node {
+    /// 0 for the seeds, then one more for every map
+    pub stage: usize,
+    pub category: String,
+    pub start: u64,
+    pub len: u64,
+}
+
+/// Numbers moving from one node to the next stage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    /// Index of the node they come from
+    pub source: usize,
+    /// Index of the node they go to, made of exactly these numbers
+    pub target: usize,
+    /// How many numbers
+    pub value: u64,
+    /// Where they start in the source node
+    pub from: u64,
+}
+
+/// How the seed ranges split and shift through every map, as the nodes and links of a
+/// Sankey diagram. Pieces are never merged back, so every node but the seeds has exactly
+/// one link coming in.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Flow {
+    pub nodes: Vec<Node>,
+    pub links: Vec<Link>,
+}
+
+impl Flow {
+    /// `categories` has one more name than there are `stages`
+    pub fn new(categories: &[String], stages: &[RangeMap], seeds: &[(u64, u64)]) -> Flow {
+        assert!(categories.len() == stages.len() + 1);
+        let mut flow = Flow::default();
+        let mut frontier = Vec::new();
+        for &(start, len) in seeds.iter() {
+            frontier.push(flow.nodes.len());
+            flow.nodes.push(Node {
+                stage: 0,
+                category: categories[0].clone(),
+                start,
+                len,
+            });
+        }
+        for (i, stage) in stages.iter().enumerate() {
+            let mut next = Vec::new();
+            for source in frontier {
+                let node = &flow.nodes[source];
+                for piece in stage.split(node.start, node.len) {
+                    next.push(flow.nodes.len());
+                    flow.links.push(Link {
+                        source,
+                        target: flow.nodes.len(),
+                        value: piece.len,
+                        from: piece.src,
+                    });
+                    flow.nodes.push(Node {
+                        stage: i + 1,
+                        category: categories[i + 1].clone(),
+                        start: piece.dst,
+                        len: piece.len,
+                    });
+                }
+            }
+            frontier = next;
+        }
+        flow
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Every seed range as a tree, one line per piece, indented by stage
+    pub fn text(&self) -> String {
+        let mut children = vec![Vec::new(); self.nodes.len()];
+        for link in self.links.iter() {
+            children[link.source].push(link);
+        }
+        fn write(flow: &Flow, children: &[Vec<&Link>], node: usize, result: &mut String) {
+            for link in children[node].iter() {
+                let target = &flow.nodes[link.target];
+                let shift = match target.start as i128 - link.from as i128 {
+                    0 => "unchanged".to_string(),
+                    shift => format!("{:+}", shift),
+                };
+                *result += &format!(
+                    "{}{} {}..{} from {}..{} ({})\n",
+                    "  ".repeat(target.stage),
+                    target.category,
+                    target.start,
+                    target.start + target.len,
+                    link.from,
+                    link.from + link.value,
+                    shift
+                );
+                write(flow, children, link.target, result);
+            }
+        }
+        let mut result = String::new();
+        for (i, node) in self.nodes.iter().enumerate().filter(|(_, n)| n.stage == 0) {
+            result += &format!(
+                "{} {}..{}\n",
+                node.category,
+                node.start,
+                node.start + node.len
+            );
+            write(self, &children, i, &mut result);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::almanac::Almanac;
+    use crate::{input, seed_ranges};
+
+    const EXAMPLE: [&str; 33] = [
+        "seeds: 79 14 55 13",
+        "",
+        "seed-to-soil map:",
+        "50 98 2",
+        "52 50 48",
+        "",
+        "soil-to-fertilizer map:",
+        "0 15 37",
+        "37 52 2",
+        "39 0 15",
+        "",
+        "fertilizer-to-water map:",
+        "49 53 8",
+        "0 11 42",
+        "42 0 7",
+        "57 7 4",
+        "",
+        "water-to-light map:",
+        "88 18 7",
+        "18 25 70",
+        "",
+        "light-to-temperature map:",
+        "45 77 23",
+        "81 45 19",
+        "68 64 13",
+        "",
+        "temperature-to-humidity map:",
+        "0 69 1",
+        "1 0 69",
+        "",
+        "humidity-to-location map:",
+        "60 56 37",
+        "56 93 4",
+    ];
+
+    fn example() -> (Flow, Vec<RangeMap>) {
+        let lines = EXAMPLE.map(|l| l.to_string());
+        let input = input::parse(&lines).unwrap();
+        let almanac = Almanac::new(&input).unwrap();
+        let categories = almanac.route("seed", "location").unwrap();
+        let stages = almanac.path("seed", "location").unwrap();
+        let flow = Flow::new(&categories, &stages, &seed_ranges(&input.seeds));
+        (flow, stages)
+    }
+
+    #[test]
+    fn conserved() {
+        let (flow, stages) = example();
+        let mut incoming = vec![0; flow.nodes.len()];
+        let mut outgoing = vec![0; flow.nodes.len()];
+        for link in flow.links.iter() {
+            let (source, target) = (&flow.nodes[link.source], &flow.nodes[link.target]);
+            assert_eq!(target.stage, source.stage + 1);
+            assert!(source.start <= link.from);
+            assert!(link.from + link.value <= source.start + source.len);
+            assert_eq!(target.len, link.value);
+            // The whole piece is shifted the same way
+            let stage = &stages[source.stage];
+            for x in [link.from, link.from + link.value - 1] {
+                assert_eq!(stage.apply(x) - target.start, x - link.from);
+            }
+            incoming[link.target] += link.value;
+            outgoing[link.source] += link.value;
+        }
+        for (i, node) in flow.nodes.iter().enumerate() {
+            if node.stage > 0 {
+                assert_eq!(incoming[i], node.len, "into {:?}", node);
+            }
+            if node.stage < stages.len() {
+                assert_eq!(outgoing[i], node.len, "out of {:?}", node);
+            }
+        }
+        let locations = flow.nodes.iter().filter(|n| n.category == "location");
+        assert_eq!(locations.map(|n| n.start).min(), Some(46));
+    }
+
+    #[test]
+    fn text() {
+        let (flow, _) = example();
+        assert_eq!(
+            flow.text().lines().collect::<Vec<_>>(),
+            [
+                "seed 79..93",
+                "  soil 81..95 from 79..93 (+2)",
+                "    fertilizer 81..95 from 81..95 (unchanged)",
+                "      water 81..95 from 81..95 (unchanged)",
+                "        light 74..88 from 81..95 (-7)",
+                "          temperature 78..81 from 74..77 (+4)",
+                "            humidity 78..81 from 78..81 (unchanged)",
+                "              location 82..85 from 78..81 (+4)",
+                "          temperature 45..56 from 77..88 (-32)",
+                "            humidity 46..57 from 45..56 (+1)",
+                "              location 46..56 from 46..56 (unchanged)",
+                "              location 60..61 from 56..57 (+4)",
+                "seed 55..68",
+                "  soil 57..70 from 55..68 (+2)",
+                "    fertilizer 57..70 from 57..70 (unchanged)",
+                "      water 53..57 from 57..61 (-4)",
+                "        light 46..50 from 53..57 (-7)",
+                "          temperature 82..86 from 46..50 (+36)",
+                "            humidity 82..86 from 82..86 (unchanged)",
+                "              location 86..90 from 82..86 (+4)",
+                "      water 61..70 from 61..70 (unchanged)",
+                "        light 54..63 from 61..70 (-7)",
+                "          temperature 90..99 from 54..63 (+36)",
+                "            humidity 90..99 from 90..99 (unchanged)",
+                "              location 94..97 from 90..93 (+4)",
+                "              location 56..60 from 93..97 (-37)",
+                "              location 97..99 from 97..99 (unchanged)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::validate::{Finding, Problem};
+use serde::{Deserialize, Serialize};
+
+/// A `destination source length` line of a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub line: usize,
+    pub dst: u64,
+    pub src: u64,
+    pub len: u64,
+}
+
+/// A `seed-to-soil map:` chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Map {
+    pub line: usize,
+    pub from: String,
+    pub to: String,
+    pub entries: Vec<Entry>,
+}
+
+/// The almanac as written, keeping the line everything comes from so that problems found
+/// in a model read with `--load` still point into the input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Input {
+    pub seeds_line: usize,
+    pub seeds: Vec<u64>,
+    pub maps: Vec<Map>,
+}
+
+fn numbers(line: &str) -> Option<Vec<u64>> {
+    line.split(" ").map(|s| s.parse::<u64>().ok()).collect()
+}
+
+/// Source and destination categories of a `seed-to-soil map:` header
+fn parse_header(header: &str) -> Option<(String, String)> {
+    header
+        .strip_suffix(" map:")
+        .and_then(|names| names.split_once("-to-"))
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty() && from != to)
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+}
+
+/// Read the lines of an almanac, or report every line that doesn't fit its place
+pub fn parse(lines: &[String]) -> Result<Input, Vec<Finding>> {
+    let mut malformed = Vec::new();
+    let mut report = |line: usize, reason: &str| {
+        malformed.push(Finding {
+            line,
+            problem: Problem::Malformed(reason.to_string()),
+        })
+    };
+    // Chunks of numbered lines, between blank lines
+    let mut chunks: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut blank = true;
+    for (i, line) in lines.iter().enumerate() {
+        match (line.is_empty(), blank) {
+            (true, _) => {}
+            (false, true) => chunks.push(vec![(i + 1, line)]),
+            (false, false) => chunks.last_mut().unwrap().push((i + 1, line)),
+        }
+        blank = line.is_empty();
+    }
+    let mut chunks = chunks.into_iter();
+    let mut input = Input {
+        seeds_line: 1,
+        seeds: Vec::new(),
+        maps: Vec::new(),
+    };
+    match chunks.next().as_deref() {
+        Some([(number, line), rest @ ..]) => {
+            input.seeds_line = *number;
+            match line.strip_prefix("seeds: ").and_then(numbers) {
+                Some(seeds) => input.seeds = seeds,
+                None => report(*number, "expected seeds: <numbers>"),
+            }
+            for &(number, _) in rest {
+                report(number, "expected a blank line after the seeds");
+            }
+        }
+        _ => report(1, "expected seeds: <numbers>"),
+    }
+    for chunk in chunks {
+        let (line, header) = chunk[0];
+        let Some((from, to)) = parse_header(header) else {
+            report(line, "expected <from>-to-<to> map:");
+            continue;
+        };
+        let mut map = Map {
+            line,
+            from,
+            to,
+            entries: Vec::new(),
+        };
+        for &(line, text) in chunk[1..].iter() {
+            match numbers(text).as_deref() {
+                Some(&[dst, src, len]) => map.entries.push(Entry {
+                    line,
+                    dst,
+                    src,
+                    len,
+                }),
+                _ => report(line, "expected <destination> <source> <length>"),
+            }
+        }
+        input.maps.push(map);
+    }
+    match malformed.is_empty() {
+        true => Ok(input),
+        false => Err(malformed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::{dump, from_str, Format};
+
+    #[test]
+    fn model_round_trip() {
+        let text =
+            "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:";
+        let lines = text.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        let model = parse(&lines).unwrap();
+        assert_eq!(model.maps[0].entries[1].line, 5);
+        for format in [Format::Json, Format::Ron] {
+            let text = dump(&model, format).unwrap();
+            assert_eq!(
+                from_str::<Input>(&text, format),
+                Ok(model.clone()),
+                "{}",
+                text
+            );
+        }
+    }
+}
@@ -1,5 +1,14 @@
+mod almanac;
+mod flow;
+mod input;
+mod range_map;
+mod validate;
+
+use almanac::Almanac;
+use range_map::RangeMap;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -8,167 +17,169 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-fn divide_maps(lines: Vec<String>) -> Vec<Vec<String>> {
-    let mut chunks = Vec::new();
-    chunks.push(Vec::new());
-    for line in lines {
-        match line.as_str() {
-            "" => {
-                chunks.push(Vec::new());
+fn seed_ranges(seeds: &[u64]) -> Ranges {
+    seeds
+        .iter()
+        .step_by(2)
+        .zip(seeds.iter().skip(1).step_by(2))
+        .map(|(x, y)| (*x, *y))
+        .collect()
+}
+
+/// The stages of the almanac, from seeds to locations
+fn stages(almanac: &Almanac) -> Vec<RangeMap> {
+    almanac
+        .path("seed", "location")
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Print the map from one category to another as almanac lines, or what it makes of the
+/// numbers in `--values`
+fn print_mapping(almanac: &Almanac, query: &str) {
+    let (from, to) = query
+        .split_once(':')
+        .unwrap_or_else(|| panic!("Expected <from>:<to>, got {}", query));
+    let map = compose_stages(&almanac.path(from, to).unwrap_or_else(|e| panic!("{}", e)));
+    match common::flag_value("--values") {
+        Some(values) => {
+            for x in values.split(',').map(|s| s.parse::<u64>().unwrap()) {
+                println!("{} {} -> {} {}", from, x, to, map.apply(x));
             }
-            _ => {
-                let last = chunks.len() - 1;
-                chunks[last].push(line);
+        }
+        None => {
+            for s in map.segments() {
+                println!("{} {} {}", s.dst, s.src, s.len);
             }
         }
     }
-    chunks
 }
 
-fn apply_maps(maps: &Vec<Vec<String>>) -> Vec<u64> {
-    let seeds = maps[0][0]
-        .split(" ")
-        .skip(1)
-        .map(|s| s.parse::<u64>().unwrap())
-        .collect::<Vec<_>>();
-    let mut result = seeds.clone();
-    for m in maps[1..].iter() {
-        let mut flags = vec![false; seeds.len()];
-        for line in m[1..].iter() {
-            let parts = line.split(" ").collect::<Vec<_>>();
-            assert!(parts.len() == 3);
-            let parts = parts
-                .into_iter()
-                .map(|s| s.parse::<u64>().unwrap())
-                .collect::<Vec<_>>();
-            let (dst, src, len) = (parts[0], parts[1], parts[2]);
-            result
-                .iter_mut()
-                .enumerate()
-                .for_each(|(i, x)| match flags[i] {
-                    true => {}
-                    false => {
-                        if *x >= src && *x < src + len {
-                            *x -= src;
-                            *x += dst;
-                            flags[i] = true;
-                        }
-                    }
-                });
-        }
-    }
-    result
+fn apply_maps(stages: &[RangeMap], seeds: &[u64]) -> Vec<u64> {
+    seeds
+        .iter()
+        .map(|&x| stages.iter().fold(x, |x, stage| stage.apply(x)))
+        .collect()
 }
 
-fn determine_intersection(
-    s: u64,
-    l: u64,
-    src: u64,
-    len: u64,
-) -> (Vec<(u64, u64)>, Vec<(u64, u64)>) {
-    let e = s + l;
-    let end = src + len;
-    if s >= end || e <= src {
-        // Outside the range src..src+len
-        (vec![], vec![(s, l)])
-    } else if s <= src && e >= end {
-        // The range src..src+len is inside the original range
-        (
-            vec![(src, len)],
-            vec![
-                if s < src { vec![(s, src - s)] } else { vec![] },
-                if e > end {
-                    vec![(end, e - end)]
-                } else {
-                    vec![]
-                },
-            ]
-            .concat(),
-        )
-    } else if s <= src {
-        // The left part of src..src+len intersects with the right part of original range
-        (
-            vec![(src, e - src)],
-            if s < src { vec![(s, src - s)] } else { vec![] },
-        )
-    } else if e >= end {
-        // The right part of src..src+len intersects with the left part of original range
-        (
-            vec![(s, end - s)],
-            if e > end {
-                vec![(end, e - end)]
-            } else {
-                vec![]
-            },
-        )
-    } else if s > src && e < end {
-        // The original range is inside the range src..src+len
-        (vec![(s, l)], vec![])
-    } else {
-        panic!(
-            "Unexpected case: s={}, l={}, src={}, len={}",
-            s, l, src, len
-        );
-    }
+type Ranges = Vec<(u64, u64)>;
+
+fn apply_range_maps(stages: &[RangeMap], seeds: &[(u64, u64)]) -> Ranges {
+    stages
+        .iter()
+        .fold(seeds.to_vec(), |ranges, stage| stage.apply_ranges(&ranges))
 }
 
-fn apply_range_maps(maps: &Vec<Vec<String>>) -> Vec<(u64, u64)> {
-    let seeds = maps[0][0]
-        .split(" ")
-        .skip(1)
-        .map(|s| s.parse::<u64>().unwrap())
-        .collect::<Vec<_>>();
-    let seeds = seeds
+/// All the stages folded into a single seed to location map
+fn compose_stages(stages: &[RangeMap]) -> RangeMap {
+    stages
         .iter()
-        .step_by(2)
-        .zip(seeds.iter().skip(1).step_by(2))
-        .map(|(x, y)| (*x, *y))
-        .collect::<Vec<_>>();
-    let mut unmapped = seeds.clone();
-    let mut mapped = Vec::new();
-    for m in maps[1..].iter() {
-        for line in m[1..].iter() {
-            let parts = line.split(" ").collect::<Vec<_>>();
-            assert!(parts.len() == 3);
-            let parts = parts
-                .into_iter()
-                .map(|s| s.parse::<u64>().unwrap())
-                .collect::<Vec<_>>();
-            let (dst, src, len) = (parts[0], parts[1], parts[2]);
-            let mut unmatched = Vec::new();
-            unmapped.iter().for_each(|&(s, l)| {
-                let (ma, um) = determine_intersection(s, l, src, len);
-                mapped.extend(ma.into_iter().map(|(s, l)| (s + dst - src, l)));
-                unmatched.extend(um);
-            });
-            unmapped = unmatched;
-        }
-        mapped.extend(unmapped);
-        unmapped = Vec::new();
-        // Concat continuous segments
-        mapped.sort();
-        mapped.into_iter().for_each(|(rs, rl)| {
-            if unmapped.len() > 0 {
-                let (ls, ll) = unmapped.last_mut().unwrap();
-                if *ls + *ll == rs {
-                    *ll += rl;
-                } else {
-                    unmapped.push((rs, rl));
-                }
-            } else {
-                unmapped.push((rs, rl));
-            }
-        });
-        mapped = Vec::new();
-    }
-    unmapped
+        .fold(RangeMap::default(), |map, stage| map.compose(stage))
+}
+
+/// Walk the locations upwards, mapping each one back to its seed, until one of them is planted
+fn lowest_location_backwards(stages: &[RangeMap], seeds: &[(u64, u64)]) -> u64 {
+    let backwards = stages.iter().rev().fold(RangeMap::default(), |map, stage| {
+        map.compose(&stage.inverse())
+    });
+    // Past the last segment every location is its own seed, and past the last seed range none
+    // of those is planted, so the lowest location is below both
+    let bound = backwards
+        .segments()
+        .iter()
+        .map(|s| s.source().end)
+        .chain(seeds.iter().map(|&(s, l)| s + l))
+        .max()
+        .unwrap_or(0);
+    (0..bound)
+        .find(|&location| {
+            let seed = backwards.apply(location);
+            seeds.iter().any(|&(s, l)| seed >= s && seed - s < l)
+        })
+        .expect("No seeds are planted")
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let maps = divide_maps(lines);
-    let result = apply_maps(&maps);
-    println!("{}", result.iter().reduce(|x, y| x.min(y)).unwrap());
-    let result = apply_range_maps(&maps);
-    println!("{}", result.first().unwrap().0);
+    common::init_tracing();
+    let input = info_span!("parse").in_scope(|| {
+        common::model::try_load_or_exit(|| {
+            input::parse(&read_lines("input.txt")).map_err(|findings| {
+                let lines = findings.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+                lines.join("\n")
+            })
+        })
+    });
+    let findings = validate::validate(&input);
+    if common::has_flag("--validate") {
+        for finding in findings.iter() {
+            println!("{}", finding);
+        }
+        if findings.iter().any(|f| f.is_error()) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if findings.iter().any(|f| f.is_error()) {
+        for finding in findings.iter() {
+            eprintln!("{}", finding);
+        }
+        std::process::exit(1);
+    }
+    let almanac = match Almanac::new(&input) {
+        Ok(almanac) => almanac,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    let seeds = input.seeds;
+    if let Some(query) = common::flag_value("--map") {
+        print_mapping(&almanac, &query);
+        return;
+    }
+    let stages = stages(&almanac);
+    if let Some(format) = common::flag_value("--flow") {
+        let categories = almanac.route("seed", "location").unwrap();
+        let flow = flow::Flow::new(&categories, &stages, &seed_ranges(&seeds));
+        match format.as_str() {
+            "json" => println!("{}", flow.json()),
+            "text" => print!("{}", flow.text()),
+            _ => {
+                eprintln!("Unknown format: {}, expected json or text", format);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    info_span!("part1").in_scope(|| {
+        let result = common::strategy::choose(&[
+            ("points", &|| {
+                let result = apply_maps(&stages, &seeds);
+                *result.iter().reduce(|x, y| x.min(y)).unwrap()
+            }),
+            ("ranges", &|| {
+                let seeds = seeds.iter().map(|&s| (s, 1)).collect::<Vec<_>>();
+                apply_range_maps(&stages, &seeds).first().unwrap().0
+            }),
+            ("composed", &|| {
+                let map = compose_stages(&stages);
+                seeds.iter().map(|&s| map.apply(s)).min().unwrap()
+            }),
+        ]);
+        println!("{}", result);
+    });
+    info_span!("part2").in_scope(|| {
+        let seeds = seed_ranges(&seeds);
+        let result = common::strategy::choose(&[
+            ("ranges", &|| {
+                apply_range_maps(&stages, &seeds).first().unwrap().0
+            }),
+            ("composed", &|| {
+                let map = compose_stages(&stages);
+                map.apply_ranges(&seeds).first().unwrap().0
+            }),
+            // There are too many seeds to map one by one, so map the locations back instead
+            ("points", &|| lowest_location_backwards(&stages, &seeds)),
+        ]);
+        println!("{}", result);
+    });
 }
@@ -0,0 +1,411 @@
+use crate::Ranges;
+use interval::{Interval, IntervalSet};
+
+/// `len` numbers from `src` sent to the same number of numbers from `dst`. Both ranges end
+/// within u64, so numbers can be moved between them without overflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Segment {
+    pub src: u64,
+    pub dst: u64,
+    pub len: u64,
+}
+
+impl Segment {
+    pub fn source(&self) -> Interval {
+        Interval::from_len(self.src, self.len)
+    }
+
+    pub fn destination(&self) -> Interval {
+        Interval::from_len(self.dst, self.len)
+    }
+
+    /// Where `x`, a number of the source, is sent
+    fn image(&self, x: u64) -> u64 {
+        self.dst
+            .checked_add(x - self.src)
+            .expect("Segment ends past u64")
+    }
+
+    /// Which number of the source is sent to `y`, a number of the destination
+    fn preimage(&self, y: u64) -> u64 {
+        self.src
+            .checked_add(y - self.dst)
+            .expect("Segment ends past u64")
+    }
+}
+
+/// A stage of the almanac as a function on numbers: sorted, disjoint segments, with every
+/// number outside them mapped to itself
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    segments: Vec<Segment>,
+}
+
+/// Sort by source and join the segments that carry on from each other
+fn coalesce(mut segments: Vec<Segment>) -> Vec<Segment> {
+    segments.sort();
+    let mut result: Vec<Segment> = Vec::new();
+    for s in segments {
+        match result.last_mut() {
+            Some(l) if l.source().end == s.src && l.destination().end == s.dst => l.len += s.len,
+            _ => result.push(s),
+        }
+    }
+    result
+}
+
+impl RangeMap {
+    /// From `(dst, src, len)` entries as in the almanac. Where sources overlap, the entry
+    /// listed first wins, as when looking the entries up one after the other. Panics if a
+    /// range ends past u64.
+    pub fn new(entries: impl IntoIterator<Item = (u64, u64, u64)>) -> RangeMap {
+        let mut segments: Vec<Segment> = Vec::new();
+        for (dst, src, len) in entries {
+            src.checked_add(len).expect("source range overflows u64");
+            dst.checked_add(len)
+                .expect("destination range overflows u64");
+            let entry = Segment { src, dst, len };
+            let mut free = vec![entry.source()];
+            for s in segments.iter() {
+                free = free
+                    .iter()
+                    .flat_map(|f| f.difference(&s.source()))
+                    .collect();
+            }
+            segments.extend(free.into_iter().filter(|f| !f.is_empty()).map(|f| Segment {
+                src: f.start,
+                dst: entry.image(f.start),
+                len: f.len(),
+            }));
+        }
+        segments.retain(|s| s.src != s.dst);
+        RangeMap {
+            segments: coalesce(segments),
+        }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn apply(&self, x: u64) -> u64 {
+        let i = self.segments.partition_point(|s| s.src <= x);
+        match i.checked_sub(1).map(|i| self.segments[i]) {
+            Some(s) if x - s.src < s.len => s.image(x),
+            _ => x,
+        }
+    }
+
+    /// Cut `start..start + len` where the map changes, as non-empty segments (identity ones
+    /// included) in no particular order
+    fn pieces(&self, start: u64, len: u64) -> Vec<Segment> {
+        let mut unmapped = vec![Interval::from_len(start, len)];
+        unmapped.retain(|u| !u.is_empty());
+        let mut pieces = Vec::new();
+        for seg in self.segments.iter() {
+            let source = seg.source();
+            let mut unmatched = Vec::new();
+            for u in unmapped.iter() {
+                pieces.extend(u.intersection(&source).map(|i| Segment {
+                    src: i.start,
+                    dst: seg.image(i.start),
+                    len: i.len(),
+                }));
+                unmatched.extend(u.difference(&source));
+            }
+            unmapped = unmatched;
+        }
+        pieces.extend(unmapped.into_iter().map(|u| Segment {
+            src: u.start,
+            dst: u.start,
+            len: u.len(),
+        }));
+        pieces
+    }
+
+    /// Cut `start..start + len` where the map changes, in order
+    pub fn split(&self, start: u64, len: u64) -> Vec<Segment> {
+        let mut pieces = self.pieces(start, len);
+        pieces.sort();
+        pieces
+    }
+
+    /// The image of `(start, len)` ranges, sorted and with overlapping or continuous ranges
+    /// joined
+    pub fn apply_ranges(&self, ranges: &[(u64, u64)]) -> Ranges {
+        ranges
+            .iter()
+            .flat_map(|&(s, l)| self.pieces(s, l))
+            .map(|p| Interval::from_len(p.dst, p.len))
+            .collect::<IntervalSet>()
+            .intervals()
+            .iter()
+            .map(|i| (i.start, i.len()))
+            .collect()
+    }
+
+    /// This map followed by `other`
+    pub fn compose(&self, other: &RangeMap) -> RangeMap {
+        // What this map moves goes on through `other`...
+        let moved = self.segments.iter().flat_map(|s| {
+            other
+                .pieces(s.dst, s.len)
+                .into_iter()
+                .map(move |p| Segment {
+                    src: s.preimage(p.src),
+                    dst: p.dst,
+                    len: p.len,
+                })
+        });
+        // ...and what it leaves in place only goes through `other`
+        let kept = other.segments.iter().flat_map(|o| {
+            self.pieces(o.src, o.len)
+                .into_iter()
+                .filter(|p| p.src == p.dst)
+                .map(move |p| Segment {
+                    src: p.src,
+                    dst: o.image(p.src),
+                    len: p.len,
+                })
+        });
+        let mut segments = moved.chain(kept).collect::<Vec<_>>();
+        segments.retain(|s| s.src != s.dst && s.len > 0);
+        RangeMap {
+            segments: coalesce(segments),
+        }
+    }
+
+    /// Whether every number is the image of exactly one number: the segments' destinations
+    /// cover the same numbers as their sources, which they can only do without overlapping
+    pub fn is_bijection(&self) -> bool {
+        let sources = self
+            .segments
+            .iter()
+            .map(|s| s.source())
+            .collect::<IntervalSet>();
+        let destinations = self
+            .segments
+            .iter()
+            .map(|s| s.destination())
+            .collect::<IntervalSet>();
+        sources == destinations
+    }
+
+    /// Map back from destinations to sources. Numbers outside every destination range map to
+    /// themselves, so this is only the inverse of a map that is a bijection, as the stages
+    /// of a well-formed almanac are.
+    pub fn inverse(&self) -> RangeMap {
+        RangeMap::new(self.segments.iter().map(|s| (s.src, s.dst, s.len)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Numbers the random maps move around, with room on either side for identity
+    const DOMAIN: u64 = 40;
+
+    /// A fixed linear congruential generator, so failures can be replayed
+    fn generator(mut seed: u64) -> impl FnMut(u64) -> u64 {
+        move |n| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        }
+    }
+
+    /// Up to five entries anywhere in the domain, overlapping or not
+    fn random_map(next: &mut impl FnMut(u64) -> u64) -> RangeMap {
+        RangeMap::new((0..next(6)).map(|_| (next(DOMAIN), next(DOMAIN), next(10))))
+    }
+
+    /// The domain cut into blocks that are shuffled, so that every number is hit once
+    fn random_bijection(next: &mut impl FnMut(u64) -> u64) -> RangeMap {
+        let mut cuts = (0..next(6)).map(|_| next(DOMAIN)).collect::<Vec<_>>();
+        cuts.extend([0, DOMAIN]);
+        cuts.sort();
+        cuts.dedup();
+        let mut blocks = cuts
+            .windows(2)
+            .map(|w| (w[0], w[1] - w[0]))
+            .collect::<Vec<_>>();
+        for i in (1..blocks.len()).rev() {
+            blocks.swap(i, next(i as u64 + 1) as usize);
+        }
+        let mut dst = 0;
+        let entries = blocks.into_iter().map(|(src, len)| {
+            dst += len;
+            (dst - len, src, len)
+        });
+        RangeMap::new(entries.collect::<Vec<_>>())
+    }
+
+    fn example() -> RangeMap {
+        RangeMap::new([(50, 98, 2), (52, 50, 48)])
+    }
+
+    #[test]
+    fn new() {
+        let map = example();
+        assert_eq!(
+            map.segments(),
+            [
+                Segment {
+                    src: 50,
+                    dst: 52,
+                    len: 48
+                },
+                Segment {
+                    src: 98,
+                    dst: 50,
+                    len: 2
+                },
+            ]
+        );
+        assert_eq!(
+            [0, 49, 50, 97, 98, 99, 100].map(|x| map.apply(x)),
+            [0, 49, 52, 99, 50, 51, 100]
+        );
+    }
+
+    #[test]
+    fn first_entry_wins() {
+        let map = RangeMap::new([(100, 10, 10), (200, 5, 10), (300, 12, 3)]);
+        assert_eq!(
+            [4, 5, 9, 10, 14, 19, 20].map(|x| map.apply(x)),
+            [4, 200, 204, 100, 104, 109, 20]
+        );
+    }
+
+    #[test]
+    fn identity_and_continuing_entries() {
+        assert_eq!(RangeMap::new([(5, 5, 10)]), RangeMap::default());
+        assert_eq!(
+            RangeMap::new([(100, 0, 5), (105, 5, 5)]),
+            RangeMap::new([(100, 0, 10)])
+        );
+    }
+
+    #[test]
+    fn compose() {
+        // Soil to fertilizer of the puzzle's example
+        let next = RangeMap::new([(0, 15, 37), (37, 52, 2), (39, 0, 15)]);
+        let map = example().compose(&next);
+        for (seed, fertilizer) in [(79, 81), (14, 53), (55, 57), (13, 52), (98, 35), (0, 39)] {
+            assert_eq!(map.apply(seed), fertilizer);
+        }
+        assert_eq!(RangeMap::default().compose(&next), next);
+        assert_eq!(next.compose(&RangeMap::default()), next);
+    }
+
+    #[test]
+    fn inverse() {
+        let inverse = example().inverse();
+        assert_eq!(inverse, RangeMap::new([(98, 50, 2), (50, 52, 48)]));
+        assert_eq!(inverse.inverse(), example());
+    }
+
+    #[test]
+    fn is_bijection() {
+        assert!(example().is_bijection());
+        assert!(RangeMap::default().is_bijection());
+        // 0..5 is hit twice and 10..15 never
+        assert!(!RangeMap::new([(0, 10, 5)]).is_bijection());
+        assert!(!RangeMap::new([(20, 0, 5), (22, 5, 5)]).is_bijection());
+        let mut next = generator(4);
+        for _ in 0..100 {
+            assert!(random_bijection(&mut next).is_bijection());
+            let map = random_map(&mut next);
+            let mut images = (0..DOMAIN * 3).map(|x| map.apply(x)).collect::<Vec<_>>();
+            images.sort();
+            let onto = images == (0..DOMAIN * 3).collect::<Vec<_>>();
+            assert_eq!(map.is_bijection(), onto, "{:?}", map);
+        }
+    }
+
+    #[test]
+    fn compose_applies_both() {
+        let mut next = generator(1);
+        for _ in 0..500 {
+            let (a, b) = (random_map(&mut next), random_map(&mut next));
+            let composed = a.compose(&b);
+            for x in 0..DOMAIN * 2 {
+                assert_eq!(
+                    composed.apply(x),
+                    b.apply(a.apply(x)),
+                    "{:?} {:?} {}",
+                    a,
+                    b,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_round_trips() {
+        let mut next = generator(2);
+        for _ in 0..500 {
+            let map = random_bijection(&mut next);
+            let inverse = map.inverse();
+            for x in 0..DOMAIN * 2 {
+                assert_eq!(inverse.apply(map.apply(x)), x, "{:?} {}", map, x);
+                assert_eq!(map.apply(inverse.apply(x)), x, "{:?} {}", map, x);
+            }
+            assert_eq!(map.compose(&inverse), RangeMap::default());
+        }
+    }
+
+    #[test]
+    fn apply_ranges_maps_every_point() {
+        let mut next = generator(3);
+        for _ in 0..500 {
+            let map = random_map(&mut next);
+            let ranges = (0..next(4))
+                .map(|_| (next(DOMAIN * 2), next(15)))
+                .collect::<Vec<_>>();
+            let mut expected = ranges
+                .iter()
+                .flat_map(|&(s, l)| (s..s + l).map(|x| map.apply(x)))
+                .collect::<Vec<_>>();
+            expected.sort();
+            expected.dedup();
+            let image = map.apply_ranges(&ranges);
+            let points = image
+                .iter()
+                .flat_map(|&(s, l)| s..s + l)
+                .collect::<Vec<_>>();
+            assert_eq!(points, expected, "{:?} {:?}", map, ranges);
+            // Joined, so no two ranges touch
+            assert!(image.windows(2).all(|w| w[0].0 + w[0].1 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn top_of_u64() {
+        let top = u64::MAX - 10;
+        let map = RangeMap::new([(0, top, 10), (top, 0, 10)]);
+        assert_eq!(
+            [top, top + 9, 0, 9].map(|x| map.apply(x)),
+            [0, 9, top, top + 9]
+        );
+        assert_eq!(map.apply(u64::MAX), u64::MAX);
+        assert_eq!(map.compose(&map), RangeMap::default());
+        assert_eq!(map.inverse(), map);
+        assert_eq!(map.split(top - 5, 15).len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "destination range overflows u64")]
+    fn destination_past_u64() {
+        RangeMap::new([(u64::MAX - 5, 0, 10)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "source range overflows u64")]
+    fn source_past_u64() {
+        RangeMap::new([(0, u64::MAX - 5, 10)]);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::input::Input;
+use interval::{Interval, IntervalSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// A line that isn't what its place in the almanac calls for
+    Malformed(String),
+    /// `start + len` of a range doesn't fit in a `u64`
+    Overflow {
+        what: &'static str,
+        start: u64,
+        len: u64,
+    },
+    /// Source numbers `start..end` are also in the range on line `other`, which comes first
+    /// and so wins
+    Overlap { other: usize, start: u64, end: u64 },
+    /// Source numbers `start..end` outside the ranges of a map, which map to themselves
+    Gap { start: u64, end: u64 },
+}
+
+/// Something wrong, or worth knowing, about a line of the almanac
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Starting at 1, as in an editor
+    pub line: usize,
+    pub problem: Problem,
+}
+
+impl Finding {
+    /// Gaps are allowed by the puzzle, everything else makes the answers meaningless
+    pub fn is_error(&self) -> bool {
+        !matches!(self.problem, Problem::Gap { .. })
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.is_error() {
+            true => "error",
+            false => "note",
+        };
+        write!(f, "line {}: {}: ", self.line, level)?;
+        match &self.problem {
+            Problem::Malformed(reason) => write!(f, "{}", reason),
+            Problem::Overflow { what, start, len } => {
+                write!(f, "{} range {} + {} overflows u64", what, start, len)
+            }
+            Problem::Overlap { other, start, end } => write!(
+                f,
+                "sources {}..{} are already mapped on line {}",
+                start, end, other
+            ),
+            Problem::Gap { start, end } => {
+                write!(f, "sources {}..{} are mapped to themselves", start, end)
+            }
+        }
+    }
+}
+
+/// Check the numbers of an almanac that was read without problems
+pub fn validate(input: &Input) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut report = |line: usize, problem: Problem| findings.push(Finding { line, problem });
+    for pair in input.seeds.chunks(2) {
+        match *pair {
+            [start, len] if start.checked_add(len).is_none() => report(
+                input.seeds_line,
+                Problem::Overflow {
+                    what: "seed",
+                    start,
+                    len,
+                },
+            ),
+            [start] => report(
+                input.seeds_line,
+                Problem::Malformed(format!("seed range {} has no length", start)),
+            ),
+            _ => {}
+        }
+    }
+    for map in input.maps.iter() {
+        // Line and source range of the entries read so far
+        let mut sources: Vec<(usize, Interval)> = Vec::new();
+        for entry in map.entries.iter() {
+            let mut fits = true;
+            for (what, start) in [("source", entry.src), ("destination", entry.dst)] {
+                if start.checked_add(entry.len).is_none() {
+                    report(
+                        entry.line,
+                        Problem::Overflow {
+                            what,
+                            start,
+                            len: entry.len,
+                        },
+                    );
+                    fits = false;
+                }
+            }
+            if !fits || entry.len == 0 {
+                continue;
+            }
+            let source = Interval::from_len(entry.src, entry.len);
+            for &(other, earlier) in sources.iter() {
+                if let Some(i) = source.intersection(&earlier) {
+                    report(
+                        entry.line,
+                        Problem::Overlap {
+                            other,
+                            start: i.start,
+                            end: i.end,
+                        },
+                    );
+                }
+            }
+            sources.push((entry.line, source));
+        }
+        // The gaps are what's missing from every number a range can start at, before the first
+        // source, between the sources and after the last one
+        let covered = sources.iter().map(|&(_, s)| s).collect::<IntervalSet>();
+        let mut start = 0;
+        for i in covered
+            .intervals()
+            .iter()
+            .chain([&Interval::new(u64::MAX, u64::MAX)])
+        {
+            if start < i.start {
+                report(
+                    map.line,
+                    Problem::Gap {
+                        start,
+                        end: i.start,
+                    },
+                );
+            }
+            start = i.end;
+        }
+    }
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input;
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15";
+
+    fn findings(text: &str) -> Vec<String> {
+        let lines = text.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        let findings = match input::parse(&lines) {
+            Ok(input) => validate(&input),
+            Err(findings) => findings,
+        };
+        findings.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(
+            findings(EXAMPLE),
+            [
+                "line 3: note: sources 0..50 are mapped to themselves",
+                "line 3: note: sources 100..18446744073709551615 are mapped to themselves",
+                "line 7: note: sources 54..18446744073709551615 are mapped to themselves",
+            ]
+        );
+    }
+
+    #[test]
+    fn gaps() {
+        let text = "seeds: 1 2\n\na-to-b map:\n10 20 5\n30 40 5\n\nb-to-c map:\n";
+        assert_eq!(
+            findings(text),
+            [
+                "line 3: note: sources 0..20 are mapped to themselves",
+                "line 3: note: sources 25..40 are mapped to themselves",
+                "line 3: note: sources 45..18446744073709551615 are mapped to themselves",
+                "line 7: note: sources 0..18446744073709551615 are mapped to themselves",
+            ]
+        );
+        // Nothing left out
+        let whole = format!("seeds: 1 2\n\na-to-b map:\n0 0 {}", u64::MAX);
+        assert!(findings(&whole).is_empty());
+    }
+
+    #[test]
+    fn overlap() {
+        let text = "seeds: 1 2\n\na-to-b map:\n0 10 10\n100 15 10\n200 0 12";
+        let found = findings(text);
+        assert!(found
+            .contains(&"line 5: error: sources 15..20 are already mapped on line 4".to_string()));
+        assert!(found
+            .contains(&"line 6: error: sources 10..12 are already mapped on line 4".to_string()));
+    }
+
+    #[test]
+    fn overflow() {
+        let max = u64::MAX;
+        let text = format!(
+            "seeds: {} 2 5 6\n\na-to-b map:\n{} 0 2\n0 {} 2\n0 5 {}",
+            max, max, max, max
+        );
+        let found = findings(&text);
+        let expected = [
+            format!("line 1: error: seed range {} + 2 overflows u64", max),
+            format!("line 4: error: destination range {} + 2 overflows u64", max),
+            format!("line 5: error: source range {} + 2 overflows u64", max),
+            format!("line 6: error: source range 5 + {} overflows u64", max),
+        ];
+        for expected in expected {
+            assert!(found.contains(&expected), "{} in {:?}", expected, found);
+        }
+    }
+
+    #[test]
+    fn unpaired_seed() {
+        assert_eq!(
+            findings("seeds: 1 2 3\n\na-to-b map:\n0 0 1")[0],
+            "line 1: error: seed range 3 has no length"
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let text = "\n\nseeds: 1 x\nextra\n\nseed to soil\n1 2 3\n\na-to-b map:\n1 2\n1 2 3";
+        assert_eq!(
+            findings(text),
+            [
+                "line 3: error: expected seeds: <numbers>",
+                "line 4: error: expected a blank line after the seeds",
+                "line 6: error: expected <from>-to-<to> map:",
+                "line 10: error: expected <destination> <source> <length>",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        for text in ["", "\n\n"] {
+            assert_eq!(findings(text), ["line 1: error: expected seeds: <numbers>"]);
+        }
+    }
+
+    #[test]
+    fn line_numbers() {
+        // Blank lines anywhere still count
+        let text = "\nseeds: 1 2\n\n\na-to-b map:\n0 10 10\n0 10 1\n\n\nb-to-c map:\n\n";
+        let lines = text.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        let input = input::parse(&lines).unwrap();
+        assert_eq!(input.seeds_line, 2);
+        assert_eq!([input.maps[0].line, input.maps[1].line], [5, 10]);
+        assert_eq!(input.maps[0].entries[1].line, 7);
+        assert!(findings(text)
+            .contains(&"line 7: error: sources 10..11 are already mapped on line 6".to_string()));
+    }
+}
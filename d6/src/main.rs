@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -15,55 +16,105 @@ fn parse_input(lines: Vec<String>) -> Vec<(u64, u64)> {
             .last()
             .unwrap()
             .split(" ")
-            .map(|s| s.parse::<u64>())
-            .filter(|r| r.is_ok())
-            .map(|r| r.unwrap())
+            .flat_map(|s| s.parse::<u64>())
             .collect::<Vec<_>>()
     };
     let time = parse_line(lines[0].as_str());
     let distance = parse_line(lines[1].as_str());
-    return time.into_iter().zip(distance.into_iter()).collect();
+    time.into_iter().zip(distance).collect()
 }
 
-fn winning_strategies(records: Vec<(u64, u64)>) -> Vec<u64> {
-    records
-        .into_iter()
-        .map(|(t, d)| {
-            let i = (0..t / 2).find(|i| i * (t - i) > d).unwrap();
-            t - 2 * i + 1
-        })
-        .collect()
+/// Number of ways to beat the record `d` in a race of `t`, trying every hold time
+fn winning_strategies(t: u64, d: u64) -> u64 {
+    let i = (0..=t / 2).find(|i| i * (t - i) > d).unwrap();
+    t - 2 * i + 1
 }
 
-fn winning_strategies_bad_kerning(records: Vec<(u64, u64)>) -> u64 {
-    let mut time = Vec::new();
-    let mut distance = Vec::new();
-    records.into_iter().for_each(|(t, d)| {
-        time.push(t.to_string());
-        distance.push(d.to_string());
-    });
-    let time = time.concat().parse::<u64>().unwrap();
-    let distance = distance.concat().parse::<u64>().unwrap();
+/// Same as `winning_strategies`, binary searching the shortest winning hold time
+fn winning_strategies_binary(t: u64, d: u64) -> u64 {
+    // The distance only grows up to t / 2, which is assumed to win
     let mut lb = 0;
-    let mut rb = time / 2;
-    while rb - lb > 1 {
+    let mut rb = t / 2;
+    while lb < rb {
         let pivot = (lb + rb) / 2;
-        if pivot * (time - pivot) > distance {
+        if pivot * (t - pivot) > d {
             rb = pivot;
         } else {
             lb = pivot + 1;
         }
     }
-    assert!(lb * (time - lb) > distance);
-    assert!((lb - 1) * (time - lb + 1) <= distance);
-    time - 2 * lb + 1
+    assert!(lb * (t - lb) > d);
+    assert!(lb == 0 || (lb - 1) * (t - lb + 1) <= d);
+    t - 2 * lb + 1
+}
+
+/// The single race hidden behind the bad kerning of the sheet
+fn bad_kerning(records: &[(u64, u64)]) -> (u64, u64) {
+    let mut time = Vec::new();
+    let mut distance = Vec::new();
+    records.iter().for_each(|(t, d)| {
+        time.push(t.to_string());
+        distance.push(d.to_string());
+    });
+    let time = time.concat().parse::<u64>().unwrap();
+    let distance = distance.concat().parse::<u64>().unwrap();
+    (time, distance)
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let records = parse_input(lines);
-    let result = winning_strategies(records.clone()).into_iter().product::<u64>();
-    println!("{}", result);
-    let result = winning_strategies_bad_kerning(records);
-    println!("{}", result);
+    common::init_tracing();
+    let records = info_span!("parse").in_scope(|| parse_input(read_lines("input.txt")));
+    info_span!("part1").in_scope(|| {
+        let product =
+            |ways: fn(u64, u64) -> u64| records.iter().map(|&(t, d)| ways(t, d)).product::<u64>();
+        let result = common::strategy::choose(&[
+            ("binary", &|| product(winning_strategies_binary)),
+            ("linear", &|| product(winning_strategies)),
+        ]);
+        println!("{}", result);
+    });
+    info_span!("part2").in_scope(|| {
+        let (t, d) = bad_kerning(&records);
+        let result = common::strategy::choose(&[
+            ("binary", &|| winning_strategies_binary(t, d)),
+            ("linear", &|| winning_strategies(t, d)),
+        ]);
+        println!("{}", result);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<(u64, u64)> {
+        parse_input(vec![
+            "Time:      7  15   30".to_string(),
+            "Distance:  9  40  200".to_string(),
+        ])
+    }
+
+    #[test]
+    fn example_answers() {
+        let records = example();
+        for ways in [winning_strategies, winning_strategies_binary] {
+            let product = records.iter().map(|&(t, d)| ways(t, d)).product::<u64>();
+            assert_eq!(product, 288);
+            let (t, d) = bad_kerning(&records);
+            assert_eq!(ways(t, d), 71503);
+        }
+    }
+
+    #[test]
+    fn binary_matches_linear() {
+        for t in 1..80 {
+            let best = (t / 2) * (t - t / 2);
+            // Every record that can still be beaten, down to the closest call
+            for d in 0..best {
+                let expected = (0..=t).filter(|i| i * (t - i) > d).count() as u64;
+                assert_eq!(winning_strategies(t, d), expected, "t {} d {}", t, d);
+                assert_eq!(winning_strategies_binary(t, d), expected, "t {} d {}", t, d);
+            }
+        }
+    }
 }
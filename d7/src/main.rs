@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -22,7 +24,7 @@ enum Hand {
 
 fn get_strength(ch: char, joker: bool) -> u64 {
     match ch {
-        _ if ch.is_digit(10) => ch.to_digit(10).unwrap() as u64,
+        _ if ch.is_ascii_digit() => ch.to_digit(10).unwrap() as u64,
         'T' | 't' => 10,
         'J' | 'j' => {
             if joker {
@@ -38,19 +40,32 @@ fn get_strength(ch: char, joker: bool) -> u64 {
     }
 }
 
-fn parse_line(line: &str, joker: bool) -> (Hand, Vec<u64>, u64) {
-    // Hand type, card strengths, bid value
+/// A hand as dealt, with J read as jack or as joker only when it's ranked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Game {
+    cards: String,
+    bid: u64,
+}
+
+fn parse_line(line: &str) -> Game {
     let parts = line.split_whitespace().collect::<Vec<_>>();
     assert!(parts.len() == 2);
-    let cards = parts[0]
+    Game {
+        cards: parts[0].to_string(),
+        bid: parts[1].parse::<u64>().unwrap(),
+    }
+}
+
+fn rank(cards: &str, joker: bool) -> (Hand, Vec<u64>) {
+    // Hand type, card strengths
+    let cards = cards
         .chars()
         .map(|ch| get_strength(ch, joker))
         .collect::<Vec<_>>();
-    let bid = parts[1].parse::<u64>().unwrap();
     let mut card_count = BTreeMap::new();
     cards
         .iter()
-        .for_each(|&card| *card_count.entry(card).or_insert(0 as u64) += 1);
+        .for_each(|&card| *card_count.entry(card).or_insert(0_u64) += 1);
     if card_count.contains_key(&1) && card_count.len() > 1 {
         // The best strategy to use the joker is to use it as the card with the most count
         // Unless the joker is the only card, then we don't need to do anything
@@ -64,7 +79,7 @@ fn parse_line(line: &str, joker: bool) -> (Hand, Vec<u64>, u64) {
             .0;
         *card_count.entry(max_card).or_insert(0) += num_joker;
     }
-    let mut counts = vec![0 as u64; 6];
+    let mut counts = vec![0_u64; 6];
     card_count
         .iter()
         .for_each(|(_, &count)| counts[count as usize] += 1);
@@ -78,35 +93,64 @@ fn parse_line(line: &str, joker: bool) -> (Hand, Vec<u64>, u64) {
         _ => Hand::HighCard,
     };
 
-    (hand, cards, bid)
+    (hand, cards)
 }
 
-fn main() {
-    let lines = read_lines("input.txt");
-    let mut games = lines
+fn winnings(games: &[Game], joker: bool) -> u64 {
+    let mut ranked = games
         .iter()
-        .map(|line| parse_line(&line, false))
+        .map(|game| (rank(&game.cards, joker), game.bid))
         .collect::<Vec<_>>();
-    games.sort();
-    println!(
-        "{:?}",
-        games
-            .into_iter()
-            .enumerate()
-            .map(|(i, (_, _, bid))| { (i + 1) as u64 * bid })
-            .sum::<u64>()
-    );
-    let mut games = lines
+    ranked.sort();
+    ranked
         .into_iter()
-        .map(|line| parse_line(&line, true))
-        .collect::<Vec<_>>();
-    games.sort();
-    println!(
-        "{:?}",
-        games
-            .into_iter()
-            .enumerate()
-            .map(|(i, (_, _, bid))| { (i + 1) as u64 * bid })
-            .sum::<u64>()
-    );
+        .enumerate()
+        .map(|(i, (_, bid))| (i + 1) as u64 * bid)
+        .sum()
+}
+
+fn main() {
+    common::init_tracing();
+    let games = info_span!("parse").in_scope(|| {
+        common::model::load_or_exit(|| {
+            read_lines("input.txt")
+                .iter()
+                .map(|line| parse_line(line))
+                .collect::<Vec<_>>()
+        })
+    });
+    info_span!("part1").in_scope(|| {
+        println!("{:?}", winnings(&games, false));
+    });
+    info_span!("part2").in_scope(|| {
+        println!("{:?}", winnings(&games, true));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::{dump, from_str, Format};
+
+    #[test]
+    fn model_round_trip() {
+        let model = [
+            "32T3K 765",
+            "T55J5 684",
+            "KK677 28",
+            "KTJJT 220",
+            "QQQJA 483",
+        ]
+        .map(parse_line)
+        .to_vec();
+        for format in [Format::Json, Format::Ron] {
+            let text = dump(&model, format).unwrap();
+            assert_eq!(
+                from_str::<Vec<Game>>(&text, format),
+                Ok(model.clone()),
+                "{}",
+                text
+            );
+        }
+    }
 }
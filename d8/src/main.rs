@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -9,13 +11,13 @@ fn read_lines(file_name: &str) -> Vec<String> {
     contents.lines().map(|s| s.to_string()).collect()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum Instruction {
     Left,
     Right,
 }
 
-fn parse_map(lines: Vec<String>) -> (Vec<Instruction>, HashMap<String, (String, String)>) {
+fn parse_map(lines: Vec<String>) -> (Vec<Instruction>, BTreeMap<String, (String, String)>) {
     let sequence = lines[0]
         .chars()
         .map(|ch| match ch {
@@ -24,7 +26,7 @@ fn parse_map(lines: Vec<String>) -> (Vec<Instruction>, HashMap<String, (String,
             _ => panic!("Invalid instruction"),
         })
         .collect::<Vec<_>>();
-    let mut map = HashMap::new();
+    let mut map = BTreeMap::new();
     for line in lines.into_iter().skip(2) {
         let parts = line.split(" = ").collect::<Vec<_>>();
         assert!(parts.len() == 2);
@@ -43,7 +45,7 @@ fn parse_map(lines: Vec<String>) -> (Vec<Instruction>, HashMap<String, (String,
     (sequence, map)
 }
 
-fn follow_map(instruction: Vec<Instruction>, map: HashMap<String, (String, String)>) -> u64 {
+fn follow_map(instruction: Vec<Instruction>, map: BTreeMap<String, (String, String)>) -> u64 {
     let mut steps = 0;
     let mut position = String::from("AAA");
     while position != "ZZZ" {
@@ -59,7 +61,7 @@ fn follow_map(instruction: Vec<Instruction>, map: HashMap<String, (String, Strin
 
 fn follow_map_simultaneous(
     instruction: Vec<Instruction>,
-    map: HashMap<String, (String, String)>,
+    map: BTreeMap<String, (String, String)>,
 ) -> u64 {
     let positions = map
         .keys()
@@ -97,10 +99,42 @@ fn follow_map_simultaneous(
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let (sequence, map) = parse_map(lines);
-    let steps = follow_map(sequence.to_owned(), map.to_owned());
-    println!("{}", steps);
-    let steps = follow_map_simultaneous(sequence, map);
-    println!("{}", steps);
+    common::init_tracing();
+    let (sequence, map) = info_span!("parse")
+        .in_scope(|| common::model::load_or_exit(|| parse_map(read_lines("input.txt"))));
+    info_span!("part1").in_scope(|| {
+        let steps = follow_map(sequence.to_owned(), map.to_owned());
+        println!("{}", steps);
+    });
+    info_span!("part2").in_scope(|| {
+        let steps = follow_map_simultaneous(sequence, map);
+        println!("{}", steps);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::{dump, from_str, Format};
+
+    #[test]
+    fn model_round_trip() {
+        let lines = [
+            "LLR",
+            "",
+            "AAA = (BBB, BBB)",
+            "BBB = (AAA, ZZZ)",
+            "ZZZ = (ZZZ, ZZZ)",
+        ];
+        let model = parse_map(lines.map(|l| l.to_string()).to_vec());
+        for format in [Format::Json, Format::Ron] {
+            let text = dump(&model, format).unwrap();
+            assert_eq!(
+                from_str::<(Vec<Instruction>, BTreeMap<String, (String, String)>)>(&text, format),
+                Ok(model.clone()),
+                "{}",
+                text
+            );
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::io::Read;
+use tracing::info_span;
 
 fn read_lines(file_name: &str) -> Vec<String> {
     let mut file = File::open(file_name).unwrap();
@@ -13,23 +14,16 @@ fn parse_line(line: &str) -> Vec<i64> {
     parts.iter().map(|s| s.parse::<i64>().unwrap()).collect()
 }
 
-fn predict_values(sequence: &Vec<i64>) -> (i64, i64) {
+fn predict_values(sequence: &[i64]) -> (i64, i64) {
     let mut diffs = Vec::new();
     diffs.push(sequence.to_owned());
     while diffs.last().unwrap().iter().any(|&x| x != 0) {
         let last = diffs.last().unwrap();
-        diffs.push(
-            last.iter()
-                .skip(1)
-                .zip(last.into_iter())
-                .map(|(a, b)| a - b)
-                .collect(),
-        );
+        diffs.push(last.iter().skip(1).zip(last).map(|(a, b)| a - b).collect());
     }
     let mut next = 0;
     let mut prev = 0;
-    while diffs.len() > 0 {
-        let last = diffs.pop().unwrap();
+    while let Some(last) = diffs.pop() {
         next += last.last().unwrap();
         prev = last.first().unwrap() - prev;
     }
@@ -37,22 +31,29 @@ fn predict_values(sequence: &Vec<i64>) -> (i64, i64) {
 }
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let sequences: Vec<Vec<i64>> = lines.iter().map(|l| parse_line(l)).collect();
-    println!(
-        "{:?}",
-        sequences
-            .iter()
-            .map(predict_values)
-            .map(|(_, l)| l)
-            .sum::<i64>()
-    );
-    println!(
-        "{:?}",
-        sequences
-            .iter()
-            .map(predict_values)
-            .map(|(f, _)| f)
-            .sum::<i64>()
-    );
+    common::init_tracing();
+    let sequences: Vec<Vec<i64>> = info_span!("parse").in_scope(|| {
+        let lines = read_lines("input.txt");
+        lines.iter().map(|l| parse_line(l)).collect()
+    });
+    info_span!("part1").in_scope(|| {
+        println!(
+            "{:?}",
+            sequences
+                .iter()
+                .map(|s| predict_values(s))
+                .map(|(_, l)| l)
+                .sum::<i64>()
+        )
+    });
+    info_span!("part2").in_scope(|| {
+        println!(
+            "{:?}",
+            sequences
+                .iter()
+                .map(|s| predict_values(s))
+                .map(|(f, _)| f)
+                .sum::<i64>()
+        )
+    });
 }
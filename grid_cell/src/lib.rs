@@ -0,0 +1,65 @@
+use std::fmt;
+
+pub use grid_cell_derive::GridCell;
+
+/// A character that does not map to any variant of a grid cell enum
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InvalidCell(pub char);
+
+impl fmt::Display for InvalidCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid grid character: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidCell {}
+
+/// A single grid tile that can be read from and written back to one character.
+///
+/// Implemented by `#[derive(GridCell)]`. Every variant needs a character:
+///
+/// ```compile_fail
+/// #[derive(Clone, Copy, grid_cell::GridCell)]
+/// enum Cell {
+///     #[cell('.')]
+///     Empty,
+///     Wall,
+/// }
+/// ```
+///
+/// and variants with fields need one for every value of them:
+///
+/// ```compile_fail
+/// #[derive(Clone, Copy, grid_cell::GridCell)]
+/// enum Cell {
+///     #[cell('/', false)]
+///     Mirror(bool),
+/// }
+/// ```
+///
+/// ```
+/// #[derive(Clone, Copy, grid_cell::GridCell)]
+/// enum Cell {
+///     #[cell('/', false)]
+///     #[cell('\\', true)]
+///     Mirror(bool),
+/// }
+/// assert_eq!(char::from(Cell::Mirror(true)), '\\');
+/// ```
+pub trait GridCell: TryFrom<char, Error = InvalidCell> + Into<char> + Copy {}
+
+pub fn parse_row<T: GridCell>(line: &str) -> Result<Vec<T>, InvalidCell> {
+    line.chars().map(T::try_from).collect()
+}
+
+pub fn parse_grid<T: GridCell>(lines: &[String]) -> Result<Vec<Vec<T>>, InvalidCell> {
+    lines.iter().map(|l| parse_row(l)).collect()
+}
+
+/// Inverse of `parse_grid`, one line per row without a trailing newline
+pub fn render_grid<T: GridCell>(grid: &[Vec<T>]) -> String {
+    grid.iter()
+        .map(|row| row.iter().map(|&c| c.into()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,65 @@
+use grid_cell::{parse_grid, render_grid, GridCell, InvalidCell};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, GridCell)]
+enum Tile {
+    #[cell('.')]
+    Empty,
+    #[cell('#')]
+    Wall,
+    #[cell('/', false)]
+    #[cell('\\', true)]
+    Mirror(bool),
+    #[cell('+', false, false)]
+    #[cell('-', true, false)]
+    #[cell('|', false, true)]
+    #[cell('*', true, true)]
+    Pipe(bool, bool),
+}
+
+const CHARS: &str = ".#/\\+-|*";
+
+#[test]
+fn every_variant_round_trips() {
+    let tiles = [
+        Tile::Empty,
+        Tile::Wall,
+        Tile::Mirror(false),
+        Tile::Mirror(true),
+        Tile::Pipe(false, false),
+        Tile::Pipe(true, false),
+        Tile::Pipe(false, true),
+        Tile::Pipe(true, true),
+    ];
+    assert_eq!(tiles.len(), CHARS.chars().count());
+    for (tile, ch) in tiles.into_iter().zip(CHARS.chars()) {
+        assert_eq!(char::from(tile), ch);
+        assert_eq!(Tile::try_from(ch), Ok(tile));
+        assert_eq!(tile.to_string(), ch.to_string());
+    }
+}
+
+#[test]
+fn every_char_round_trips() {
+    for ch in CHARS.chars() {
+        assert_eq!(char::from(Tile::try_from(ch).unwrap()), ch);
+    }
+}
+
+#[test]
+fn unknown_char() {
+    assert_eq!(Tile::try_from('x'), Err(InvalidCell('x')));
+    assert_eq!(
+        parse_grid::<Tile>(&["..".to_string(), ".x".to_string()]),
+        Err(InvalidCell('x'))
+    );
+}
+
+#[test]
+fn grid_round_trips() {
+    let text = "#.#/\\\n+-|*..#\n......";
+    let lines = text.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+    let grid = parse_grid::<Tile>(&lines).unwrap();
+    assert_eq!(grid[0][3], Tile::Mirror(false));
+    assert_eq!(grid[1][3], Tile::Pipe(true, true));
+    assert_eq!(render_grid(&grid), text);
+}
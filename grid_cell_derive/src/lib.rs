@@ -0,0 +1,140 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::BTreeSet;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Lit, Token, Type, Variant};
+
+/// Derive `TryFrom<char>`, `From<Self> for char`, `Display` and `grid_cell::GridCell`
+/// for a grid cell enum.
+///
+/// Every variant needs at least one `#[cell('x')]` attribute. Variants with
+/// fields list the literal payload after the character, e.g. `#[cell('/', false)]`.
+#[proc_macro_derive(GridCell, attributes(cell))]
+pub fn derive_grid_cell(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Conversion back to a char needs a character for every value of the variant, so its fields
+/// must all be `bool`s and the `#[cell]` attributes must list every combination of them
+fn check_payloads(variant: &Variant, payloads: &BTreeSet<Vec<String>>) -> syn::Result<()> {
+    let fields = match &variant.fields {
+        Fields::Unnamed(f) => &f.unnamed,
+        _ => return Ok(()),
+    };
+    let all_bools = fields.iter().all(|f| match &f.ty {
+        Type::Path(p) => p.path.is_ident("bool"),
+        _ => false,
+    });
+    if !all_bools || payloads.len() != 1 << fields.len() {
+        return Err(syn::Error::new_spanned(
+            variant,
+            format!(
+                "#[cell] attributes of `{}` must list every value of its fields, which must be bools",
+                variant.ident
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "GridCell can only be derived for enums",
+        ));
+    };
+    let mut chars = Vec::new();
+    let mut values = Vec::new();
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        // Payloads listed for this variant, to check that they cover every value
+        let mut payloads = BTreeSet::new();
+        let cells = variant
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("cell"))
+            .map(|a| a.parse_args_with(Punctuated::<Lit, Token![,]>::parse_terminated))
+            .collect::<syn::Result<Vec<_>>>()?;
+        if cells.is_empty() {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "missing #[cell('...')] attribute",
+            ));
+        }
+        for lits in cells {
+            let mut lits = lits.into_iter();
+            let ch = match lits.next() {
+                Some(Lit::Char(ch)) => ch,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "#[cell] expects a char literal first",
+                    ))
+                }
+            };
+            if chars.iter().any(|c: &syn::LitChar| c.value() == ch.value()) {
+                return Err(syn::Error::new_spanned(ch, "duplicate cell character"));
+            }
+            let args = lits.collect::<Vec<_>>();
+            let value = match &variant.fields {
+                Fields::Unit if args.is_empty() => quote!(#name::#ident),
+                Fields::Unnamed(f) if f.unnamed.len() == args.len() => {
+                    quote!(#name::#ident(#(#args),*))
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "#[cell] arguments must match the fields of the variant",
+                    ))
+                }
+            };
+            payloads.insert(
+                args.iter()
+                    .map(|a| quote!(#a).to_string())
+                    .collect::<Vec<_>>(),
+            );
+            chars.push(ch);
+            values.push(value);
+        }
+        check_payloads(variant, &payloads)?;
+    }
+
+    Ok(quote! {
+        impl ::core::convert::TryFrom<char> for #name {
+            type Error = ::grid_cell::InvalidCell;
+
+            fn try_from(ch: char) -> ::core::result::Result<Self, Self::Error> {
+                match ch {
+                    #(#chars => ::core::result::Result::Ok(#values),)*
+                    _ => ::core::result::Result::Err(::grid_cell::InvalidCell(ch)),
+                }
+            }
+        }
+
+        impl ::core::convert::From<#name> for char {
+            fn from(cell: #name) -> char {
+                match cell {
+                    #(#values => #chars,)*
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let ch = match self {
+                    #(#values => #chars,)*
+                };
+                ::core::write!(f, "{}", ch)
+            }
+        }
+
+        impl ::grid_cell::GridCell for #name {}
+    })
+}
@@ -0,0 +1,134 @@
+use crate::Interval;
+
+/// The points whose every coordinate is in the interval of its axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub axes: [Interval; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(axes: [Interval; N]) -> Cuboid<N> {
+        Cuboid { axes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|a| a.is_empty())
+    }
+
+    /// Number of points inside. Panics if it doesn't fit in a `u128`.
+    pub fn volume(&self) -> u128 {
+        self.axes.iter().fold(1u128, |v, a| {
+            v.checked_mul(a.len() as u128)
+                .expect("Cuboid volume overflows")
+        })
+    }
+
+    pub fn contains(&self, point: [u64; N]) -> bool {
+        self.axes.iter().zip(point).all(|(a, x)| a.contains(x))
+    }
+
+    /// The points in both, `None` if there are none
+    pub fn intersection(&self, other: &Cuboid<N>) -> Option<Cuboid<N>> {
+        let mut axes = self.axes;
+        for (axis, b) in axes.iter_mut().zip(other.axes.iter()) {
+            *axis = axis.intersection(b)?;
+        }
+        Some(Cuboid { axes })
+    }
+
+    /// The non-empty parts where the coordinate along `axis` is below `at`, and from `at` on
+    pub fn split(&self, axis: usize, at: u64) -> (Option<Cuboid<N>>, Option<Cuboid<N>>) {
+        let (below, above) = self.axes[axis].split_at(at);
+        let with = |interval: Interval| {
+            let mut axes = self.axes;
+            axes[axis] = interval;
+            Cuboid { axes }
+        };
+        let non_empty = |c: &Cuboid<N>| !c.is_empty();
+        (
+            below.map(with).filter(non_empty),
+            above.map(with).filter(non_empty),
+        )
+    }
+
+    /// The points of `self` that aren't in `other`, as disjoint cuboids. Slabs are cut off
+    /// one axis after the other, so there are at most `2 * N` of them.
+    pub fn difference(&self, other: &Cuboid<N>) -> Vec<Cuboid<N>> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let Some(common) = self.intersection(other) else {
+            return vec![*self];
+        };
+        let mut result = Vec::new();
+        let mut rest = *self;
+        for axis in 0..N {
+            let (below, middle) = rest.split(axis, common.axes[axis].start);
+            result.extend(below);
+            let (middle, above) = middle.unwrap().split(axis, common.axes[axis].end);
+            result.extend(above);
+            rest = middle.unwrap();
+        }
+        result
+    }
+
+    /// Both as one cuboid, if they're the same along all axes but one, where they overlap or
+    /// touch
+    pub fn union(&self, other: &Cuboid<N>) -> Option<Cuboid<N>> {
+        if self.is_empty() {
+            return Some(*other);
+        }
+        if other.is_empty() {
+            return Some(*self);
+        }
+        let differing = (0..N)
+            .filter(|&i| self.axes[i] != other.axes[i])
+            .collect::<Vec<_>>();
+        match differing[..] {
+            [] => Some(*self),
+            [axis] => self.axes[axis].union(&other.axes[axis]).map(|interval| {
+                let mut axes = self.axes;
+                axes[axis] = interval;
+                Cuboid { axes }
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The points of all the cuboids, as few disjoint cuboids as merging neighbours gives
+pub fn union<const N: usize>(cuboids: &[Cuboid<N>]) -> Vec<Cuboid<N>> {
+    let mut disjoint: Vec<Cuboid<N>> = Vec::new();
+    for c in cuboids {
+        let mut pieces = vec![*c];
+        for d in disjoint.iter() {
+            pieces = pieces.iter().flat_map(|p| p.difference(d)).collect();
+        }
+        disjoint.extend(pieces);
+    }
+    coalesce(disjoint)
+}
+
+/// Merge disjoint cuboids that make a cuboid together, until no two do
+pub fn coalesce<const N: usize>(cuboids: Vec<Cuboid<N>>) -> Vec<Cuboid<N>> {
+    let mut result = cuboids
+        .into_iter()
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>();
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'pairs: for i in 0..result.len() {
+            for j in i + 1..result.len() {
+                if let Some(u) = result[i].union(&result[j]) {
+                    result[i] = u;
+                    result.swap_remove(j);
+                    merged = true;
+                    break 'pairs;
+                }
+            }
+        }
+    }
+    result.sort();
+    result
+}
@@ -0,0 +1,164 @@
+pub mod cuboid;
+
+pub use cuboid::Cuboid;
+
+/// The numbers `start..end`, empty when `start == end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Interval {
+    pub fn new(start: u64, end: u64) -> Interval {
+        assert!(
+            start <= end,
+            "Interval ends before it starts: {}..{}",
+            start,
+            end
+        );
+        Interval { start, end }
+    }
+
+    /// The `len` numbers from `start`, as the almanac of day 5 writes them
+    pub fn from_len(start: u64, len: u64) -> Interval {
+        let end = start
+            .checked_add(len)
+            .unwrap_or_else(|| panic!("Interval {} + {} overflows", start, len));
+        Interval::new(start, end)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        self.start <= x && x < self.end
+    }
+
+    /// The numbers in both, `None` if there are none
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let (start, end) = (self.start.max(other.start), self.end.min(other.end));
+        (start < end).then_some(Interval { start, end })
+    }
+
+    /// The numbers of `self` that aren't in `other`: the non-empty parts before and after
+    /// `other`, in order
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if self.intersection(other).is_none() {
+            return [*self].into_iter().filter(|i| !i.is_empty()).collect();
+        }
+        [
+            Interval::new(self.start, other.start.max(self.start)),
+            Interval::new(other.end.min(self.end), self.end),
+        ]
+        .into_iter()
+        .filter(|i| !i.is_empty())
+        .collect()
+    }
+
+    /// Both as one interval, if they overlap or touch
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        match (self.is_empty(), other.is_empty()) {
+            (true, _) => Some(*other),
+            (_, true) => Some(*self),
+            _ if self.start <= other.end && other.start <= self.end => Some(Interval {
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The non-empty parts below `at` and from `at` on
+    pub fn split_at(&self, at: u64) -> (Option<Interval>, Option<Interval>) {
+        let at = at.clamp(self.start, self.end);
+        (
+            Some(Interval::new(self.start, at)).filter(|i| !i.is_empty()),
+            Some(Interval::new(at, self.end)).filter(|i| !i.is_empty()),
+        )
+    }
+}
+
+/// Sorted, non-empty intervals that neither overlap nor touch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+/// Sort and join the intervals that overlap or touch, dropping the empty ones
+pub fn coalesce(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.retain(|i| !i.is_empty());
+    intervals.sort();
+    let mut result: Vec<Interval> = Vec::new();
+    for i in intervals {
+        match result.last_mut() {
+            Some(last) if i.start <= last.end => last.end = last.end.max(i.end),
+            _ => result.push(i),
+        }
+    }
+    result
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> IntervalSet {
+        IntervalSet {
+            intervals: coalesce(iter.into_iter().collect()),
+        }
+    }
+}
+
+impl IntervalSet {
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many numbers there are in the set
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(|i| i.len()).sum()
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        let i = self.intervals.partition_point(|i| i.end <= x);
+        self.intervals.get(i).is_some_and(|i| i.contains(x))
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.intervals
+            .iter()
+            .chain(other.intervals.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        // Both are sorted, so a merge pass meets every overlapping pair
+        let (mut i, mut j) = (0, 0);
+        let mut result = Vec::new();
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            result.extend(a.intersection(&b));
+            match a.end <= b.end {
+                true => i += 1,
+                false => j += 1,
+            }
+        }
+        IntervalSet { intervals: result }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = self.intervals.clone();
+        for b in other.intervals.iter() {
+            result = result.iter().flat_map(|a| a.difference(b)).collect();
+        }
+        IntervalSet { intervals: result }
+    }
+}
@@ -0,0 +1,174 @@
+use interval::cuboid::{coalesce, union};
+use interval::{Cuboid, Interval};
+use std::collections::BTreeSet;
+
+/// Every cuboid with all its ends in `0..=max`, empty ones included
+fn all_cuboids<const N: usize>(max: u64) -> Vec<Cuboid<N>> {
+    let intervals = (0..=max)
+        .flat_map(|s| (s..=max).map(move |e| Interval::new(s, e)))
+        .collect::<Vec<_>>();
+    let mut result = vec![Vec::new()];
+    for _ in 0..N {
+        result = result
+            .into_iter()
+            .flat_map(|axes: Vec<Interval>| {
+                intervals.iter().map(move |&i| {
+                    let mut axes = axes.clone();
+                    axes.push(i);
+                    axes
+                })
+            })
+            .collect();
+    }
+    result
+        .into_iter()
+        .map(|axes| Cuboid::new(axes.try_into().unwrap()))
+        .collect()
+}
+
+fn points<const N: usize>(c: &Cuboid<N>) -> BTreeSet<[u64; N]> {
+    let mut result = vec![[0; N]];
+    for (axis, interval) in c.axes.iter().enumerate() {
+        result = result
+            .into_iter()
+            .flat_map(|p| {
+                (interval.start..interval.end).map(move |x| {
+                    let mut p = p;
+                    p[axis] = x;
+                    p
+                })
+            })
+            .collect();
+    }
+    result.into_iter().collect()
+}
+
+fn all_points<const N: usize>(cuboids: &[Cuboid<N>]) -> BTreeSet<[u64; N]> {
+    cuboids.iter().flat_map(points).collect()
+}
+
+fn assert_disjoint<const N: usize>(cuboids: &[Cuboid<N>]) {
+    let total = cuboids.iter().map(|c| c.volume()).sum::<u128>();
+    assert_eq!(
+        total as usize,
+        all_points(cuboids).len(),
+        "{:?} overlap",
+        cuboids
+    );
+    assert!(
+        cuboids.iter().all(|c| !c.is_empty()),
+        "{:?} has an empty cuboid",
+        cuboids
+    );
+}
+
+fn pairs<const N: usize>(max: u64) {
+    let cuboids = all_cuboids::<N>(max);
+    for a in cuboids.iter() {
+        let pa = points(a);
+        assert_eq!(a.volume() as usize, pa.len());
+        assert_eq!(a.is_empty(), pa.is_empty());
+        for p in all_points(&all_cuboids::<N>(max + 1)[..1]) {
+            assert_eq!(a.contains(p), pa.contains(&p));
+        }
+        for axis in 0..N {
+            for at in 0..=max + 1 {
+                let (below, above) = a.split(axis, at);
+                let (eb, ea) = pa.iter().partition::<BTreeSet<_>, _>(|p| p[axis] < at);
+                assert_eq!(below.map(|b| points(&b)).unwrap_or_default(), eb);
+                assert_eq!(above.map(|b| points(&b)).unwrap_or_default(), ea);
+                assert!(below.is_none_or(|b| !b.is_empty()));
+                assert!(above.is_none_or(|b| !b.is_empty()));
+            }
+        }
+        for b in cuboids.iter() {
+            let pb = points(b);
+            let both = pa.intersection(&pb).copied().collect::<BTreeSet<_>>();
+            let got = a.intersection(b);
+            assert_eq!(
+                got.map(|c| points(&c)).unwrap_or_default(),
+                both,
+                "{:?} {:?}",
+                a,
+                b
+            );
+            assert!(got.is_none_or(|c| !c.is_empty()));
+
+            let difference = a.difference(b);
+            assert!(difference.len() <= 2 * N);
+            assert_disjoint(&difference);
+            let expected = pa.difference(&pb).copied().collect::<BTreeSet<_>>();
+            assert_eq!(all_points(&difference), expected, "{:?} {:?}", a, b);
+
+            let either = pa.union(&pb).copied().collect::<BTreeSet<_>>();
+            if let Some(u) = a.union(b) {
+                assert_eq!(points(&u), either, "{:?} {:?}", a, b);
+            }
+            let u = union(&[*a, *b]);
+            assert_disjoint(&u);
+            assert_eq!(all_points(&u), either);
+        }
+    }
+}
+
+#[test]
+fn pairs_1d() {
+    pairs::<1>(6);
+}
+
+#[test]
+fn pairs_2d() {
+    pairs::<2>(3);
+}
+
+#[test]
+fn pairs_3d() {
+    pairs::<3>(2);
+}
+
+#[test]
+fn union_of_three() {
+    let cuboids = all_cuboids::<2>(2);
+    for a in cuboids.iter() {
+        for b in cuboids.iter() {
+            for c in cuboids.iter() {
+                let u = union(&[*a, *b, *c]);
+                assert_disjoint(&u);
+                assert_eq!(all_points(&u), all_points(&[*a, *b, *c]));
+            }
+        }
+    }
+}
+
+#[test]
+fn coalescing() {
+    // A 3x3 square cut into unit cells merges back into one cuboid
+    let cells = (0..3)
+        .flat_map(|x| {
+            (0..3).map(move |y| Cuboid::new([Interval::from_len(x, 1), Interval::from_len(y, 1)]))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        coalesce(cells),
+        vec![Cuboid::new([Interval::new(0, 3), Interval::new(0, 3)])]
+    );
+    // An L shape can't be one cuboid
+    let l = coalesce(vec![
+        Cuboid::new([Interval::new(0, 2), Interval::new(0, 1)]),
+        Cuboid::new([Interval::new(0, 1), Interval::new(1, 2)]),
+    ]);
+    assert_eq!(l.len(), 2);
+    assert_disjoint(&l);
+}
+
+#[test]
+fn large_volume() {
+    let c = Cuboid::new([Interval::new(0, u64::MAX); 2]);
+    assert_eq!(c.volume(), u64::MAX as u128 * u64::MAX as u128);
+}
+
+#[test]
+#[should_panic]
+fn volume_overflow() {
+    Cuboid::new([Interval::new(0, u64::MAX); 3]).volume();
+}
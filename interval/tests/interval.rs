@@ -0,0 +1,199 @@
+use interval::{coalesce, Interval, IntervalSet};
+use std::collections::BTreeSet;
+
+const MAX: u64 = 8;
+
+/// Every interval with both ends in `0..=MAX`, empty ones included
+fn all_intervals() -> Vec<Interval> {
+    (0..=MAX)
+        .flat_map(|s| (s..=MAX).map(move |e| Interval::new(s, e)))
+        .collect()
+}
+
+fn points(i: &Interval) -> BTreeSet<u64> {
+    (i.start..i.end).collect()
+}
+
+fn set_points(set: &IntervalSet) -> BTreeSet<u64> {
+    set.intervals().iter().flat_map(points).collect()
+}
+
+/// The set of the numbers below `MAX` whose bit is on in `mask`
+fn from_mask(mask: u32) -> (IntervalSet, BTreeSet<u64>) {
+    let numbers = (0..MAX)
+        .filter(|&x| mask >> x & 1 == 1)
+        .collect::<BTreeSet<_>>();
+    let set = numbers.iter().map(|&x| Interval::new(x, x + 1)).collect();
+    (set, numbers)
+}
+
+fn assert_canonical(intervals: &[Interval]) {
+    for i in intervals {
+        assert!(!i.is_empty(), "{:?} has an empty interval", intervals);
+    }
+    for w in intervals.windows(2) {
+        assert!(
+            w[0].end < w[1].start,
+            "{:?} isn't sorted and apart",
+            intervals
+        );
+    }
+}
+
+#[test]
+fn basics() {
+    for i in all_intervals() {
+        assert_eq!(i.len() as usize, points(&i).len());
+        assert_eq!(i.is_empty(), points(&i).is_empty());
+        for x in 0..=MAX + 1 {
+            assert_eq!(i.contains(x), points(&i).contains(&x), "{:?} {}", i, x);
+        }
+        assert_eq!(Interval::from_len(i.start, i.len()), i);
+    }
+}
+
+#[test]
+fn intersection() {
+    for a in all_intervals() {
+        for b in all_intervals() {
+            let expected = points(&a)
+                .intersection(&points(&b))
+                .copied()
+                .collect::<BTreeSet<_>>();
+            let got = a.intersection(&b);
+            assert_eq!(
+                got.map(|i| points(&i)).unwrap_or_default(),
+                expected,
+                "{:?} {:?}",
+                a,
+                b
+            );
+            assert!(got.is_none_or(|i| !i.is_empty()));
+            assert_eq!(got, b.intersection(&a));
+        }
+    }
+}
+
+#[test]
+fn difference() {
+    for a in all_intervals() {
+        for b in all_intervals() {
+            let expected = points(&a)
+                .difference(&points(&b))
+                .copied()
+                .collect::<BTreeSet<_>>();
+            let got = a.difference(&b);
+            assert_canonical(&got);
+            assert_eq!(
+                got.iter().flat_map(points).collect::<BTreeSet<_>>(),
+                expected,
+                "{:?} {:?}",
+                a,
+                b
+            );
+        }
+    }
+}
+
+#[test]
+fn union() {
+    for a in all_intervals() {
+        for b in all_intervals() {
+            let expected = points(&a)
+                .union(&points(&b))
+                .copied()
+                .collect::<BTreeSet<_>>();
+            match a.union(&b) {
+                Some(u) => assert_eq!(points(&u), expected, "{:?} {:?}", a, b),
+                // Only when there's a number of neither between them
+                None => assert!(a.end < b.start || b.end < a.start, "{:?} {:?}", a, b),
+            }
+            assert_eq!(
+                a.union(&b).map(|u| points(&u)),
+                b.union(&a).map(|u| points(&u))
+            );
+        }
+    }
+}
+
+#[test]
+fn split_at() {
+    for i in all_intervals() {
+        for at in 0..=MAX + 1 {
+            let (below, above) = i.split_at(at);
+            let expected = points(&i)
+                .into_iter()
+                .partition::<BTreeSet<_>, _>(|&x| x < at);
+            assert_eq!(below.map(|b| points(&b)).unwrap_or_default(), expected.0);
+            assert_eq!(above.map(|a| points(&a)).unwrap_or_default(), expected.1);
+            assert!(below.is_none_or(|b| !b.is_empty()));
+            assert!(above.is_none_or(|a| !a.is_empty()));
+        }
+    }
+}
+
+#[test]
+fn coalescing() {
+    let intervals = all_intervals();
+    for a in intervals.iter() {
+        for b in intervals.iter() {
+            for c in intervals.iter().step_by(3) {
+                let got = coalesce(vec![*a, *b, *c]);
+                assert_canonical(&got);
+                let expected = [a, b, c]
+                    .into_iter()
+                    .flat_map(points)
+                    .collect::<BTreeSet<_>>();
+                assert_eq!(
+                    got.iter().flat_map(points).collect::<BTreeSet<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn sets() {
+    for m in 0..1 << MAX {
+        let (a, pa) = from_mask(m);
+        assert_canonical(a.intervals());
+        assert_eq!(set_points(&a), pa);
+        assert_eq!(a.len() as usize, pa.len());
+        assert_eq!(a.is_empty(), pa.is_empty());
+        for x in 0..=MAX {
+            assert_eq!(a.contains(x), pa.contains(&x));
+        }
+        for n in 0..1 << MAX {
+            let (b, pb) = from_mask(n);
+            let checks = [
+                (a.union(&b), pa.union(&pb).copied().collect::<BTreeSet<_>>()),
+                (a.intersection(&b), pa.intersection(&pb).copied().collect()),
+                (a.difference(&b), pa.difference(&pb).copied().collect()),
+            ];
+            for (got, expected) in checks {
+                assert_canonical(got.intervals());
+                assert_eq!(set_points(&got), expected, "{:b} {:b}", m, n);
+            }
+        }
+    }
+}
+
+#[test]
+fn extremes() {
+    let all = Interval::new(0, u64::MAX);
+    assert_eq!(all.len(), u64::MAX);
+    assert!(all.contains(u64::MAX - 1));
+    assert!(!all.contains(u64::MAX));
+    assert_eq!(
+        all.difference(&Interval::new(1, u64::MAX - 1)),
+        vec![Interval::new(0, 1), Interval::new(u64::MAX - 1, u64::MAX)]
+    );
+    assert_eq!(all.split_at(u64::MAX), (Some(all), None));
+}
+
+#[test]
+#[should_panic]
+fn from_len_overflow() {
+    Interval::from_len(u64::MAX, 1);
+}